@@ -1,17 +1,57 @@
 toolbar-button-home = Home
 toolbar-button-new = New
 toolbar-button-open = Open
+toolbar-button-open-with = Open with…
+toolbar-button-compare = Compare
+toolbar-button-diff = Diff
 toolbar-button-close-all = Close all
 
 file-loading = Loading...
 file-loading-error = Error loading file
 
+file-filter-all-supported = All supported files
+file-filter-text = Text
+file-filter-markdown = Markdown
+file-filter-structured = JSON/TOML
+file-filter-table = CSV/TSV
+file-filter-image = Images
+file-filter-all-files = All files
+
+drop-target-open-files = Drop files to open them
+
 menu-top-level-file = File
+menu-item-open-recent = Open Recent
 menu-item-quit = Quit
 
+recent-files-heading = Recent files
+recent-files-empty = No recent files
+recent-files-missing = File not found:
+recent-files-pin = Pin
+recent-files-remove = Remove from the list
+recent-files-remove-missing = Remove missing
+recent-files-clear = Clear unpinned
+recent-files-max-length = Number of recent files
+
 home-tab-label = Home
 home-heading = Home
 home-tab-show-on-startup = Show on startup
+home-action-new-text = New text
+home-action-new-image = New image
+home-action-open = Open
+home-recently-closed-heading = Recently closed
+home-recently-closed-empty = No recently closed tabs
+home-workspaces-heading = Workspaces
+home-workspaces-empty = No saved workspaces
+home-workspaces-files = files
+home-workspaces-remove = Delete the workspace
+home-workspaces-modified = Save the changes first, opening a workspace replaces the open documents
+home-workspaces-name-hint = Workspace name
+home-workspaces-save = Save
+home-workspaces-save-hint = Save the open documents as a workspace, opening it replaces the open documents
+home-image-decoder = Image decoder
+home-image-decoder-hint = Used for images opened after changing this setting
+home-image-decoder-image-crate = image crate
+home-image-decoder-egui-extras = egui_extras
 
 form-new-name = Name
 form-new-name-error-length = Minimum length { $min }
@@ -21,6 +61,16 @@ form-new-kind-image = Image
 form-new-kind-text = Text
 form-new-directory = Directory
 
+form-new-image-width = Width
+form-new-image-height = Height
+form-new-image-background = Background
+form-new-image-format = Format
+form-new-image-format-bmp = BMP
+form-new-image-format-png = PNG
+form-new-image-format-jpeg = JPEG
+form-new-image-jpeg-alpha-warning = JPEG does not support transparency, the background will be saved as opaque
+form-new-image-error-dimension = Must be between { $min } and { $max }
+
 form-common-button-ok = Ok
 form-common-button-cancel = Cancel
 
@@ -31,3 +81,190 @@ document-sidebar-file-path = Path
 document-sidebar-text-length = Length
 
 generic-unknown-value = Unknown
+
+
+image-toolbar-zoom-fit = Fit
+image-toolbar-zoom-actual-size = 100%
+image-toolbar-zoom-in = Zoom in
+image-toolbar-zoom-out = Zoom out
+
+image-sidebar-dimensions = Dimensions
+image-sidebar-color-type = Colour type
+image-sidebar-bit-depth = Bits per pixel
+image-sidebar-format = Format
+image-sidebar-file-size = File size
+image-sidebar-dpi = DPI
+image-sidebar-orientation = Orientation
+image-sidebar-exif-camera = Camera
+image-sidebar-exif-date = Date taken
+
+image-inspector-heading = Pixel inspector
+image-inspector-no-pixel = Hover over the image to inspect a pixel, click to pin it.
+image-inspector-pinned = Pinned
+image-inspector-unpin = Unpin
+image-inspector-clear = Clear all
+image-inspector-copy-hex = Copy as hex
+
+image-toolbar-tool-pan = Pan
+image-toolbar-tool-select = Select
+
+image-editor-heading = Edit
+image-editor-undo = Undo
+image-editor-redo = Redo
+image-editor-save = Save
+image-editor-modified = Unsaved changes
+image-editor-rotate-clockwise = Rotate right
+image-editor-rotate-counter-clockwise = Rotate left
+image-editor-flip-horizontal = Flip horizontal
+image-editor-flip-vertical = Flip vertical
+image-editor-crop = Crop to selection
+image-editor-crop-no-selection = Use the select tool to select an area first
+image-editor-resize = Resize
+image-editor-keep-aspect-ratio = Keep aspect ratio
+image-editor-apply-resize = Apply resize
+image-editor-filter-nearest = Nearest
+image-editor-filter-triangle = Linear
+image-editor-filter-catmull-rom = Cubic
+image-editor-filter-gaussian = Gaussian
+image-editor-filter-lanczos3 = Lanczos
+image-editor-canvas-size = Canvas size
+image-editor-anchor-center = Center
+image-editor-anchor-top-left = Top left
+image-editor-apply-canvas-size = Apply canvas size
+
+image-paint-heading = Paint
+image-paint-tool-pencil = Pencil
+image-paint-tool-eraser = Eraser
+image-paint-tool-fill = Fill
+image-paint-tool-line = Line
+image-paint-tool-rectangle = Rectangle
+image-paint-brush-size = Brush size
+image-paint-color = Colour
+
+comparison-tab-label = Compare
+comparison-first = First
+comparison-second = Second
+comparison-choose = Choose…
+comparison-no-image = No image selected
+comparison-choose-images = Choose two images to compare
+comparison-mode-side-by-side = Side by side
+comparison-mode-onion-skin = Onion skin
+comparison-mode-swipe = Swipe
+comparison-mode-difference = Difference
+comparison-opacity = Opacity
+comparison-swipe = Position
+comparison-changed-pixels = Changed pixels:
+comparison-psnr = PSNR:
+comparison-psnr-infinite = ∞
+comparison-different-dimensions = The images have different dimensions
+
+image-histogram-heading = Histogram
+image-histogram-rgb = RGB
+image-histogram-luminance = Luminance
+image-histogram-alpha = Alpha
+image-histogram-channel-rgba = RGBA
+image-histogram-channel-red = R
+image-histogram-channel-green = G
+image-histogram-channel-blue = B
+image-histogram-channel-alpha = A
+image-histogram-channel-luminance = Luminance
+
+animation-sidebar-frames = Frames
+animation-sidebar-duration = Duration
+animation-playback-heading = Playback
+animation-playback-play = Play
+animation-playback-pause = Pause
+animation-playback-previous = Previous frame
+animation-playback-next = Next frame
+animation-playback-frame = Frame
+animation-playback-delay = Delay
+
+image-export-heading = Export
+image-export-format = Format
+image-export-jpeg-quality = Quality
+image-export-button = Export as…
+image-export-encoding = Encoding…
+image-export-finished = Exported to
+image-export-failed = Export failed:
+
+diff-tab-label = Diff
+diff-old = Old:
+diff-new = New:
+diff-choose = Choose…
+diff-choose-sources = Choose two text documents to compare
+diff-source-disk = on disk
+diff-source-unavailable = Not available:
+diff-reload = Reload from disk
+diff-layout-side-by-side = Side by side
+diff-layout-unified = Unified
+diff-previous-hunk = Previous change
+diff-next-hunk = Next change
+diff-hunk = Change
+diff-identical = No differences
+
+markdown-layout-heading = Layout
+markdown-layout-split = Split
+markdown-layout-editor = Editor
+markdown-layout-preview = Preview
+
+structured-sidebar-format = Format
+structured-sidebar-status = Status
+structured-status-valid = Valid
+structured-status-invalid = Invalid
+structured-actions-heading = Actions
+structured-action-pretty-print = Pretty print
+structured-action-pretty-print-unsupported = Only JSON can be pretty printed, TOML would lose its comments
+structured-action-minify = Minify
+structured-action-minify-unsupported = Only JSON can be minified
+structured-error-line = Line
+structured-error-column = column
+structured-tree-unavailable = The tree is not available until the errors are fixed
+structured-selected-value = Value:
+structured-copy-path = Copy path
+structured-save-heading = Save
+structured-save-button = Save
+structured-save-modified = There are unsaved changes
+structured-save-failed = Save failed:
+
+table-sidebar-rows = Rows
+table-sidebar-columns = Columns
+table-settings-heading = Settings
+table-settings-delimiter = Delimiter
+table-settings-header = First row contains column names
+table-settings-modified = Save the changes first, changing the settings parses the file again
+table-delimiter-comma = Comma
+table-delimiter-semicolon = Semicolon
+table-delimiter-tab = Tab
+table-delimiter-pipe = Pipe
+table-save-heading = Save
+table-save-button = Save
+table-save-modified = There are unsaved changes
+table-save-failed = Save failed:
+table-sort-hint = Click to sort
+table-edit-hint = Double-click to edit
+
+hex-sidebar-size = Size
+hex-sidebar-selection = Selection
+hex-go-to-heading = Go to offset
+hex-go-to-button = Go
+hex-go-to-invalid = Enter an offset within the file, decimal or hex, e.g. 0x1F
+hex-search-heading = Search
+hex-search-mode-hex = Hex
+hex-search-mode-text = Text
+hex-search-next = Find next
+hex-search-invalid = Enter pairs of hex digits, e.g. DE AD BE EF
+hex-search-not-found = Not found
+hex-interpretation-heading = Value
+hex-interpretation-no-selection = Click a byte to see its value
+hex-interpretation-little-endian = Little endian
+hex-interpretation-big-endian = Big endian
+
+tab-context-menu-reopen-as = Reopen as…
+tab-context-menu-reopen-as-modified = Save the changes first, reopening would discard them
+open-as-text = Text
+open-as-markdown = Markdown
+open-as-json = JSON
+open-as-toml = TOML
+open-as-table = Table (CSV/TSV)
+open-as-image = Image
+open-as-hex = Hex
@@ -1,32 +1,98 @@
+use crate::app::app_tabs::compare::CompareTab;
+use crate::app::app_tabs::diff::DiffTab;
 use crate::app::app_tabs::document::DocumentTab;
 use crate::app::app_tabs::home::HomeTab;
-use crate::app::app_tabs::new::{KindChoice, NewTab};
+use crate::app::app_tabs::new::{KindChoice, NewImageOptions, NewTab};
 use crate::app::app_tabs::TabKind;
+use crate::app::recent_files::{recent_files_ui, RecentFiles, RecentlyClosed};
 use crate::app::tabs::{AppTabViewer, TabKey, Tabs};
+use crate::app::workspaces::{Workspace, WorkspaceFile, Workspaces};
+use crate::args::Args;
 use crate::context::TabContext;
+use crate::documents::animation::{self, AnimationDocument};
+use crate::documents::hex::HexDocument;
+use crate::documents::image::decoder::DecoderSettings;
 use crate::documents::image::ImageDocument;
+use crate::documents::markdown::MarkdownDocument;
+use crate::documents::structured::node::StructuredFormat;
+use crate::documents::structured::StructuredDocument;
+use crate::documents::table::TableDocument;
 use crate::documents::text::TextDocument;
-use crate::documents::{DocumentKey, DocumentKind};
-use crate::file_picker::Picker;
+use crate::documents::{files, DocumentKey, DocumentKind, OpenAs};
+use crate::file_picker::{FileFilter, Picker};
 use crate::fonts;
-use egui_dock::{DockArea, DockState, Style};
+use crate::single_instance;
+use egui::{Align2, Color32, FontId, Id, LayerId, Order, Pos2, Rect, Stroke, StrokeKind};
+use egui_dock::{DockArea, DockState, NodeIndex, Style, SurfaceIndex};
 use egui_i18n::tr;
 use egui_inbox::{UiInbox, UiInboxSender};
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use slotmap::SlotMap;
 use std::mem::MaybeUninit;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use egui_extras::install_image_loaders;
+use image::ImageFormat;
 
 const SUPPORTED_TEXT_EXTENSIONS: [&'static str; 1] = ["txt"];
-const SUPPORTED_IMAGE_EXTENSIONS: [&'static str; 4] = ["bmp", "png", "jpeg", "jpg"];
+const SUPPORTED_MARKDOWN_EXTENSIONS: [&'static str; 2] = ["md", "markdown"];
+const SUPPORTED_TABLE_EXTENSIONS: [&'static str; 2] = ["csv", "tsv"];
+
+/// the supported image extensions depend on the `formats-*` features, see `Cargo.toml`.
+fn supported_image_extensions() -> Vec<&'static str> {
+    let mut extensions: Vec<&'static str> = ImageFormat::all()
+        .filter(|format| format.reading_enabled())
+        .flat_map(|format| format.extensions_str().iter().copied())
+        .collect();
+
+    // SVG files are rasterized using `resvg`, not the `image` crate.
+    if cfg!(feature = "formats-svg") {
+        extensions.push("svg");
+    }
+
+    extensions
+}
+
+/// the filters shown in the open dialog, the first one is selected by default.
+fn open_file_filters() -> Vec<FileFilter> {
+    let filter = |name: String, extensions: Vec<&'static str>| FileFilter {
+        name,
+        extensions: extensions
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    };
+
+    let structured_extensions = vec!["json", "toml"];
+
+    let mut all_supported_extensions = vec![];
+    all_supported_extensions.extend(SUPPORTED_TEXT_EXTENSIONS);
+    all_supported_extensions.extend(SUPPORTED_MARKDOWN_EXTENSIONS);
+    all_supported_extensions.extend(structured_extensions.iter().copied());
+    all_supported_extensions.extend(SUPPORTED_TABLE_EXTENSIONS);
+    all_supported_extensions.extend(supported_image_extensions());
+
+    vec![
+        filter(tr!("file-filter-all-supported"), all_supported_extensions),
+        filter(tr!("file-filter-text"), SUPPORTED_TEXT_EXTENSIONS.to_vec()),
+        filter(tr!("file-filter-markdown"), SUPPORTED_MARKDOWN_EXTENSIONS.to_vec()),
+        filter(tr!("file-filter-structured"), structured_extensions),
+        filter(tr!("file-filter-table"), SUPPORTED_TABLE_EXTENSIONS.to_vec()),
+        filter(tr!("file-filter-image"), supported_image_extensions()),
+        // any other file can be opened using the hex viewer.
+        // Note: rfd's GTK backend turns each extension into `*.<extension>`, so there `*` becomes `*.*`, which does not
+        //       match files without an extension, the default xdg portal backend treats `*` as every file.
+        filter(tr!("file-filter-all-files"), vec!["*"]),
+    ]
+}
 
 
 pub type AppMessageSender = UiInboxSender<(MessageSource, AppMessage)>;
 
 mod app_tabs;
+mod recent_files;
 mod tabs;
+mod workspaces;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -46,6 +112,10 @@ struct AppState {
     // TODO find a better way of doing this that doesn't require this boolean
     startup_done: bool,
     file_picker: Picker,
+    /// chosen using 'Open with…', used when the file has been picked.
+    open_as: Option<OpenAs>,
+    /// the files are taken on startup.
+    args: Args,
 
     sender: UiInboxSender<(MessageSource, AppMessage)>,
     receiver: UiInbox<(MessageSource, AppMessage)>,
@@ -56,12 +126,28 @@ struct AppState {
 pub enum AppMessage {
     Refresh,
     CreateDocument(DocumentArgs),
+    /// replace the tab's document with a different kind of document for the same file.
+    ReopenAs(OpenAs),
+    /// `None` to choose the document kind using the file's extension.
+    OpenFile(PathBuf, Option<OpenAs>),
+    /// show the file picker, to open files.
+    PickFiles,
+    /// show a 'new' tab, with the kind already chosen.
+    NewDocument(KindChoice),
+    /// save the open document tabs as a workspace with the given name.
+    SaveWorkspace(String),
+    /// replace the open document tabs with the files in the workspace with the given name.
+    OpenWorkspace(String),
+    /// files from the command line of another instance, and the line to show in text documents.
+    OpenFiles(Vec<PathBuf>, Option<usize>),
 }
 
 #[derive(Debug)]
 pub enum MessageSource {
     Document(DocumentKey),
     Tab(TabKey),
+    /// see `single_instance`.
+    OtherInstance,
 }
 
 #[derive(Debug)]
@@ -69,18 +155,27 @@ pub struct DocumentArgs {
     name: String,
     directory: PathBuf,
     kind: KindChoice,
+    image_options: NewImageOptions,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct Config {
     show_home_tab_on_startup: bool,
+    pub image_decoder: DecoderSettings,
+    recent_files: RecentFiles,
+    recently_closed: RecentlyClosed,
+    workspaces: Workspaces,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             show_home_tab_on_startup: true,
+            image_decoder: DecoderSettings::default(),
+            recent_files: RecentFiles::default(),
+            recently_closed: RecentlyClosed::default(),
+            workspaces: Workspaces::default(),
         }
     }
 }
@@ -107,12 +202,14 @@ impl Default for TemplateApp {
 }
 
 impl AppState {
-    pub fn init() -> Self {
+    pub fn init(args: Args) -> Self {
         let (sender, receiver) = UiInbox::channel();
 
         Self {
             startup_done: false,
             file_picker: Picker::default(),
+            open_as: None,
+            args,
 
             sender,
             receiver,
@@ -123,7 +220,7 @@ impl AppState {
 
 impl TemplateApp {
     /// Called once before the first frame.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, args: Args) -> Self {
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
         fonts::initialize(&cc.egui_ctx);
@@ -138,9 +235,24 @@ impl TemplateApp {
             Self::default()
         };
 
-        instance.state.write(AppState::init());
+        // the settings are kept, only the tabs are not restored.
+        if args.no_restore || args.new_window {
+            info!("not restoring tabs");
+            instance = Self {
+                config: instance.config,
+                ..Self::default()
+            };
+        }
+
+        let single_instance = args.single_instance && !args.new_window;
+
+        instance.state.write(AppState::init(args));
         // Safety: `Self::state()` is now safe to call.
 
+        if single_instance {
+            single_instance::listen(instance.state().sender.clone());
+        }
+
         instance
     }
 
@@ -174,35 +286,255 @@ impl TemplateApp {
         home_tab
     }
 
-    fn add_new_tab(&mut self) {
+    /// `kind` is `None` to let the user choose.
+    fn add_new_tab(&mut self, kind: Option<KindChoice>) {
         // create a new 'new' tab
-        let tab_id = self.tabs.add(TabKind::New(NewTab::default()));
+        let tab_id = self.tabs.add(TabKind::New(NewTab::new(kind)));
+        self.tree.push_to_focused_leaf(tab_id);
+    }
+
+    fn save_workspace(&mut self, name: String) {
+        let files = self
+            .tree
+            .iter_all_tabs()
+            .filter_map(|(_surface_and_node, tab_key)| match self.tabs.get(tab_key) {
+                Some(TabKind::Document(document_tab)) if !files::is_memory_file(&document_tab.path) => {
+                    Some(WorkspaceFile {
+                        path: document_tab.path.clone(),
+                        open_as: document_tab.open_as,
+                    })
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        info!("saving workspace. name: {}, files: {}", name, files.len());
+        self.config.lock().unwrap().workspaces.save(Workspace { name, files });
+    }
+
+    fn open_workspace(&mut self, ctx: &egui::Context, name: &str) {
+        let Some(workspace) = self.config.lock().unwrap().workspaces.get(name).cloned() else {
+            return;
+        };
+
+        // replacing the documents would discard the changes
+        let modified = self
+            .state()
+            .documents
+            .lock()
+            .unwrap()
+            .values()
+            .any(DocumentKind::is_modified);
+        if modified {
+            warn!("not opening workspace, there are modified documents. name: {}", name);
+            return;
+        }
+        info!("opening workspace. name: {}", name);
+
+        // `retain_tabs` doesn't call `on_close`, the orphaned tabs are closed by `cleanup_tabs`, which calls `on_close`
+        // via `Tabs::retain_all`, removing the documents.
+        let tabs = &self.tabs;
+        self.tree
+            .retain_tabs(|tab_key| !matches!(tabs.get(tab_key), Some(TabKind::Document(_))));
+
+        for file in workspace.files {
+            self.open_file(ctx, file.path, file.open_as);
+        }
+    }
+
+    fn add_compare_tab(&mut self) {
+        let tab_id = self.tabs.add(TabKind::Compare(CompareTab::default()));
+        self.tree.push_to_focused_leaf(tab_id);
+    }
+
+    fn add_diff_tab(&mut self) {
+        let tab_id = self.tabs.add(TabKind::Diff(DiffTab::default()));
         self.tree.push_to_focused_leaf(tab_id);
     }
 
-    fn pick_file(&mut self) {
+    /// `open_as` is `None` to choose the document kind using each file's extension.
+    fn pick_files(&mut self, open_as: Option<OpenAs>) {
         if !self.state().file_picker.is_picking() {
-            self.state().file_picker.pick_file();
+            self.state().open_as = open_as;
+            // the document kind was chosen by the user, so any file can be picked, regardless of its extension.
+            let filters = match open_as {
+                Some(_) => vec![],
+                None => open_file_filters(),
+            };
+            self.state().file_picker.pick_files(filters);
+        }
+    }
+
+    /// Safety: call only once on startup, after the documents have been restored.
+    fn open_files_from_args(&mut self, ctx: &egui::Context) {
+        let files = std::mem::take(&mut self.state().args.files);
+        let line = self.state().args.line;
+
+        self.open_files(ctx, files, line);
+    }
+
+    /// the files given on the command line, of this or another instance, `line` is 1-based.
+    fn open_files(&mut self, ctx: &egui::Context, files: Vec<PathBuf>, line: Option<usize>) {
+        for path in files {
+            // relative to the directory the app was started in, made absolute so it can be opened again later.
+            let path = std::path::absolute(&path).unwrap_or(path);
+            if !path.is_file() {
+                error!("File not found. path: {:?}", path);
+                continue;
+            }
+
+            let document_key = self.open_file(ctx, path, None);
+
+            if let Some(line) = line {
+                if let Some(DocumentKind::TextDocument(text_document)) =
+                    self.state().documents.lock().unwrap().get_mut(document_key)
+                {
+                    text_document.go_to_line(line);
+                }
+            }
         }
     }
 
-    fn open_file(&mut self, ctx: &egui::Context, path: PathBuf) {
-        info!("open file. path: {:?}", path);
+    fn open_file(&mut self, ctx: &egui::Context, path: PathBuf, open_as: Option<OpenAs>) -> DocumentKey {
+        info!("open file. path: {:?}, open as: {:?}", path, open_as);
 
         let title = path.file_name().unwrap().to_string_lossy().to_string();
 
         let sender = self.state().sender.clone();
+        let config = self.config.clone();
+
+        // files that are only in memory cannot be opened again later.
+        if !files::is_memory_file(&path) {
+            config.lock().unwrap().recent_files.add(path.clone(), open_as);
+        }
+        config.lock().unwrap().recently_closed.remove(&path);
 
         let document_key = self.state().documents.lock().unwrap().insert_with_key({
             let sender = sender.clone();
 
             |new_key| {
-                Self::document_from_path(&path, ctx, sender, new_key)
+                let kind = open_as.unwrap_or_else(|| Self::open_as_for_path(&path));
+                Self::document_from_path(&path, kind, ctx, sender, new_key, &config.lock().unwrap())
             }
         });
-        let tab_kind = TabKind::Document(DocumentTab::new(title, path, document_key));
+        let tab_kind = TabKind::Document(DocumentTab::new(title, path, document_key, open_as));
 
         self.add_tab(tab_kind);
+
+        document_key
+    }
+
+    /// files dropped onto a leaf are opened in that leaf, otherwise they are opened in the focused leaf.
+    fn open_dropped_files(&mut self, ctx: &egui::Context) {
+        let (dropped_files, pointer_position) =
+            ctx.input(|input| (input.raw.dropped_files.clone(), input.pointer.latest_pos()));
+        if dropped_files.is_empty() {
+            return;
+        }
+
+        if let Some((surface_and_node, _rect)) = pointer_position.and_then(|position| self.find_leaf_at(position)) {
+            self.tree.set_focused_node_and_surface(surface_and_node);
+        }
+
+        for dropped_file in dropped_files {
+            let path = match (dropped_file.path, dropped_file.bytes) {
+                (Some(path), _) => path,
+                // the web build has no access to the file system, only to the bytes of the dropped file.
+                (None, Some(bytes)) => files::add_memory_file(&dropped_file.name, bytes),
+                (None, None) => {
+                    error!("Dropped file has no path or content. name: {}", dropped_file.name);
+                    continue;
+                }
+            };
+
+            self.open_file(ctx, path, None);
+        }
+    }
+
+    /// highlights the leaf the files will be opened in, or the whole window if they're not over a leaf.
+    fn drop_target_ui(&self, ctx: &egui::Context) {
+        let (hovering, pointer_position) =
+            ctx.input(|input| (!input.raw.hovered_files.is_empty(), input.pointer.latest_pos()));
+        if !hovering {
+            return;
+        }
+
+        let screen_rect = ctx.screen_rect();
+        let target_rect = pointer_position
+            .and_then(|position| self.find_leaf_at(position))
+            .map_or(screen_rect, |(_surface_and_node, rect)| rect);
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop_target")));
+        painter.rect_filled(screen_rect, 0.0, Color32::from_black_alpha(96));
+        painter.rect_filled(target_rect, 4.0, ctx.style().visuals.selection.bg_fill.gamma_multiply(0.25));
+        painter.rect_stroke(
+            target_rect.shrink(2.0),
+            4.0,
+            Stroke::new(2.0, ctx.style().visuals.selection.stroke.color),
+            StrokeKind::Inside,
+        );
+        painter.text(
+            target_rect.center(),
+            Align2::CENTER_CENTER,
+            tr!("drop-target-open-files"),
+            FontId::proportional(20.0),
+            ctx.style().visuals.strong_text_color(),
+        );
+    }
+
+    /// windows are shown above the main surface, so the last matching leaf is the one that is visible.
+    fn find_leaf_at(&self, position: Pos2) -> Option<((SurfaceIndex, NodeIndex), Rect)> {
+        let mut found = None;
+        for (surface_index, surface) in self.tree.iter_surfaces().enumerate() {
+            let Some(tree) = surface.node_tree() else {
+                continue;
+            };
+            for (node_index, node) in tree.iter().enumerate() {
+                if let Some(rect) = node.rect().filter(|rect| node.is_leaf() && rect.contains(position)) {
+                    found = Some(((SurfaceIndex(surface_index), NodeIndex(node_index)), rect));
+                }
+            }
+        }
+        found
+    }
+
+    /// the document is replaced, the tab stays where it is.
+    fn reopen_as(&mut self, ctx: &egui::Context, tab_key: TabKey, open_as: OpenAs) {
+        let Some(TabKind::Document(document_tab)) = self.tabs.get(&tab_key) else {
+            return;
+        };
+        let path = document_tab.path.clone();
+        let previous_key = document_tab.document_key;
+
+        // the context menu does not allow this, but the changes must never be discarded.
+        let modified = self
+            .state()
+            .documents
+            .lock()
+            .unwrap()
+            .get(previous_key)
+            .is_some_and(DocumentKind::is_modified);
+        if modified {
+            warn!("not reopening modified document. path: {:?}", path);
+            return;
+        }
+        info!("reopen file. path: {:?}, open as: {:?}", path, open_as);
+
+        let sender = self.state().sender.clone();
+        let config = self.config.clone();
+
+        let new_key = {
+            let mut documents = self.state().documents.lock().unwrap();
+            documents.remove(previous_key);
+            documents.insert_with_key(|new_key| {
+                Self::document_from_path(&path, open_as, ctx, sender, new_key, &config.lock().unwrap())
+            })
+        };
+
+        if let Some(TabKind::Document(document_tab)) = self.tabs.get_mut(&tab_key) {
+            document_tab.document_key = new_key;
+            document_tab.open_as = Some(open_as);
+        }
     }
 
     fn create_document_tab(&mut self, ctx: &mut egui::Context, args: DocumentArgs) {
@@ -221,6 +553,7 @@ impl TemplateApp {
             mut name,
             directory: mut path,
             kind,
+            image_options,
         } = args;
 
         match kind {
@@ -234,20 +567,28 @@ impl TemplateApp {
                 let document_kind = DocumentKind::TextDocument(text_document);
 
                 let document_key = self.state().documents.lock().unwrap().insert(document_kind);
-                TabKind::Document(DocumentTab::new(title, path, document_key))
+                TabKind::Document(DocumentTab::new(title, path, document_key, None))
             }
             KindChoice::Image => {
-                name.push_str(".bmp");
+                let NewImageOptions {
+                    width,
+                    height,
+                    background,
+                    format,
+                } = image_options;
+
+                name.push('.');
+                name.push_str(format.extension());
                 path.push(&name);
 
                 let title = path.file_name().unwrap().to_string_lossy().to_string();
 
-                let image_document = ImageDocument::create_new(path.clone(), ctx);
+                let image_document = ImageDocument::create_new(path.clone(), ctx, width, height, background);
                 let document_kind = DocumentKind::ImageDocument(image_document);
 
                 let document_key = self.state().documents.lock().unwrap().insert(document_kind);
-                TabKind::Document(DocumentTab::new(title, path, document_key))
-                
+                TabKind::Document(DocumentTab::new(title, path, document_key, None))
+
             },
         }
     }
@@ -297,28 +638,49 @@ impl TemplateApp {
     ///
     /// Safety: call only once on startup, before the tabs are shown.
     fn restore_documents_on_startup(&mut self, ctx: &egui::Context) {
+        // memory files are gone after restarting, and their ids are re-used, so the tabs are removed without
+        // calling `on_close`, which would remove a new memory file with the same path, or the document that now
+        // has the tab's stale document key.
+        let memory_file_tab_keys = self
+            .tabs
+            .iter()
+            .filter_map(|(tab_key, tab_kind)| match tab_kind {
+                TabKind::Document(document_tab) if files::is_memory_file(&document_tab.path) => Some(*tab_key),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for tab_key in memory_file_tab_keys {
+            info!("Removing tab for memory file from previous session. key: {:?}", tab_key);
+            if let Some(find_result) = self.tree.find_tab(&tab_key) {
+                self.tree.remove_tab(find_result);
+            }
+            self.tabs.remove(&tab_key);
+        }
+
         // we have to do this as a two-step process to above borrow-checker issues
 
-        // step 1 - find the document tabs, return the tab keys and paths.
+        // step 1 - find the document tabs, return the tab keys, paths and document kinds.
         let tab_keys_and_paths = self
             .tabs
             .iter_mut()
             .filter_map(|(tab_key, tab_kind)| match tab_kind {
                 TabKind::Document(document_tab) => {
-                    Some((tab_key.clone(), document_tab.path.clone()))
+                    Some((tab_key.clone(), document_tab.path.clone(), document_tab.open_as))
                 }
                 _ => None,
             })
             .collect::<Vec<_>>();
 
         // step 2 - store the documents and update the document key for the tab.
-        for (tab_key, path) in tab_keys_and_paths {
+        for (tab_key, path, open_as) in tab_keys_and_paths {
             let sender = self.state().sender.clone();
+            let config = self.config.clone();
 
             let new_key = self.state().documents.lock().unwrap().insert_with_key({
                 let sender = sender.clone();
                 |new_key| {
-                    Self::document_from_path(&path, ctx, sender, new_key)
+                    let kind = open_as.unwrap_or_else(|| Self::open_as_for_path(&path));
+                    Self::document_from_path(&path, kind, ctx, sender, new_key, &config.lock().unwrap())
                 }
             });
             if let TabKind::Document(ref mut document_tab) = self.tabs.get_mut(&tab_key).unwrap() {
@@ -329,17 +691,69 @@ impl TemplateApp {
         }
     }
 
-    fn document_from_path(path: &PathBuf, ctx: &egui::Context, sender: UiInboxSender<(MessageSource, AppMessage)>, new_key: DocumentKey) -> DocumentKind {
-        let extension = path.extension().unwrap().to_str().unwrap();
+    /// the document kind for a file, based on its extension, files without an extension are shown using the hex
+    /// viewer.
+    fn open_as_for_path(path: &Path) -> OpenAs {
+        // the supported extensions are lower-case, but files are often named `IMG_0001.JPG`, etc.
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let extension = extension.as_str();
 
         if SUPPORTED_TEXT_EXTENSIONS.contains(&extension) {
-            let text_document = TextDocument::from_path(path.clone(), ctx, new_key, sender);
-            DocumentKind::TextDocument(text_document)
-        } else if SUPPORTED_IMAGE_EXTENSIONS.contains(&extension) {
-            let image_document = ImageDocument::from_path(path.clone(), ctx, new_key, sender);
-            DocumentKind::ImageDocument(image_document)
+            OpenAs::Text
+        } else if SUPPORTED_MARKDOWN_EXTENSIONS.contains(&extension) {
+            OpenAs::Markdown
+        } else if let Some(format) = StructuredFormat::from_extension(extension) {
+            match format {
+                StructuredFormat::Json => OpenAs::Json,
+                StructuredFormat::Toml => OpenAs::Toml,
+            }
+        } else if SUPPORTED_TABLE_EXTENSIONS.contains(&extension) {
+            OpenAs::Table
+        } else if supported_image_extensions().contains(&extension) {
+            OpenAs::Image
         } else {
-            todo!()
+            OpenAs::Hex
+        }
+    }
+
+    fn document_from_path(path: &PathBuf, open_as: OpenAs, ctx: &egui::Context, sender: UiInboxSender<(MessageSource, AppMessage)>, new_key: DocumentKey, config: &Config) -> DocumentKind {
+        match open_as {
+            OpenAs::Text => {
+                let text_document = TextDocument::from_path(path.clone(), ctx, new_key, sender);
+                DocumentKind::TextDocument(text_document)
+            }
+            OpenAs::Markdown => {
+                let markdown_document = MarkdownDocument::from_path(path.clone(), ctx, new_key, sender);
+                DocumentKind::MarkdownDocument(markdown_document)
+            }
+            OpenAs::Json => {
+                let structured_document = StructuredDocument::from_path(path.clone(), StructuredFormat::Json, ctx, new_key, sender);
+                DocumentKind::StructuredDocument(structured_document)
+            }
+            OpenAs::Toml => {
+                let structured_document = StructuredDocument::from_path(path.clone(), StructuredFormat::Toml, ctx, new_key, sender);
+                DocumentKind::StructuredDocument(structured_document)
+            }
+            OpenAs::Table => {
+                let table_document = TableDocument::from_path(path.clone(), ctx, new_key, sender);
+                DocumentKind::TableDocument(table_document)
+            }
+            OpenAs::Image if animation::is_animation(path) => {
+                let animation_document = AnimationDocument::from_path(path.clone(), ctx, new_key, sender, config.image_decoder.clone());
+                DocumentKind::AnimationDocument(animation_document)
+            }
+            OpenAs::Image => {
+                let image_document = ImageDocument::from_path(path.clone(), ctx, new_key, sender, config.image_decoder.clone());
+                DocumentKind::ImageDocument(image_document)
+            }
+            OpenAs::Hex => {
+                let hex_document = HexDocument::from_path(path.clone(), ctx, new_key, sender);
+                DocumentKind::HexDocument(hex_document)
+            }
         }
     }
 }
@@ -347,6 +761,10 @@ impl TemplateApp {
 impl eframe::App for TemplateApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // otherwise the session of the other window would be replaced.
+        if self.state().args.new_window {
+            return;
+        }
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 
@@ -373,6 +791,31 @@ impl eframe::App for TemplateApp {
                         unreachable!()
                     }
                 }
+                (MessageSource::Tab(tab_key), AppMessage::ReopenAs(open_as)) => {
+                    self.reopen_as(ctx, tab_key, open_as);
+                }
+                (_, AppMessage::OpenFile(path, open_as)) => {
+                    self.open_file(ctx, path, open_as);
+                }
+                (_, AppMessage::PickFiles) => {
+                    self.pick_files(None);
+                }
+                (_, AppMessage::NewDocument(kind)) => {
+                    self.add_new_tab(Some(kind));
+                }
+                (_, AppMessage::SaveWorkspace(name)) => {
+                    self.save_workspace(name);
+                }
+                (_, AppMessage::OpenWorkspace(name)) => {
+                    self.open_workspace(ctx, &name);
+                }
+                (MessageSource::OtherInstance, AppMessage::OpenFiles(files, line)) => {
+                    self.open_files(ctx, files, line);
+
+                    // the user expects to see the files they just opened.
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                }
                 (source, AppMessage::Refresh) => {
                     // nothing to do, we're already refreshing at this point.
                     debug!("refresh message received. source: {:?}", source);
@@ -387,26 +830,49 @@ impl eframe::App for TemplateApp {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
 
+            let mut open_recent = None;
             egui::MenuBar::new().ui(ui, |ui| {
                 // NOTE: no File->Quit on web pages!
                 let is_web = cfg!(target_arch = "wasm32");
-                if !is_web {
-                    ui.menu_button(tr!("menu-top-level-file"), |ui| {
+                ui.menu_button(tr!("menu-top-level-file"), |ui| {
+                    ui.menu_button(tr!("menu-item-open-recent"), |ui| {
+                        open_recent = recent_files_ui(ui, &mut self.config.lock().unwrap().recent_files);
+                        if open_recent.is_some() {
+                            ui.close();
+                        }
+                    });
+                    if !is_web {
+                        ui.separator();
                         if ui.button(tr!("menu-item-quit")).clicked() {
                             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
-                    });
-                    ui.add_space(16.0);
-                }
+                    }
+                });
+                ui.add_space(16.0);
 
                 egui::widgets::global_theme_preference_buttons(ui);
             });
 
+            if let Some(recent_file) = open_recent {
+                self.open_file(ctx, recent_file.path, recent_file.open_as);
+            }
+
             egui::Frame::new().show(ui, |ui| {
                 ui.horizontal(|ui| {
                     let home_button = ui.button(tr!("toolbar-button-home"));
                     let new_button = ui.button(tr!("toolbar-button-new"));
                     let open_button = ui.button(tr!("toolbar-button-open"));
+                    let mut open_with = None;
+                    ui.menu_button(tr!("toolbar-button-open-with"), |ui| {
+                        for open_as in OpenAs::ALL {
+                            if ui.button(open_as.label()).clicked() {
+                                open_with = Some(open_as);
+                                ui.close();
+                            }
+                        }
+                    });
+                    let compare_button = ui.button(tr!("toolbar-button-compare"));
+                    let diff_button = ui.button(tr!("toolbar-button-diff"));
                     let close_all_button = ui.button(tr!("toolbar-button-close-all"));
 
                     if home_button.clicked() {
@@ -414,11 +880,23 @@ impl eframe::App for TemplateApp {
                     }
 
                     if new_button.clicked() {
-                        self.add_new_tab();
+                        self.add_new_tab(None);
                     }
 
                     if open_button.clicked() {
-                        self.pick_file()
+                        self.pick_files(None)
+                    }
+
+                    if let Some(open_as) = open_with {
+                        self.pick_files(Some(open_as))
+                    }
+
+                    if compare_button.clicked() {
+                        self.add_compare_tab();
+                    }
+
+                    if diff_button.clicked() {
+                        self.add_diff_tab();
                     }
 
                     if close_all_button.clicked() {
@@ -435,6 +913,7 @@ impl eframe::App for TemplateApp {
 
             self.show_home_tab_on_startup();
             self.restore_documents_on_startup(ctx);
+            self.open_files_from_args(ctx);
         }
 
 
@@ -461,10 +940,17 @@ impl eframe::App for TemplateApp {
             .style(Style::from_egui(ctx.style().as_ref()))
             .show(ctx, &mut my_tab_viewer);
 
-        if let Ok(picked_file) = self.state().file_picker.picked() {
+        self.open_dropped_files(ctx);
+        self.drop_target_ui(ctx);
+
+        if let Ok(picked_files) = self.state().file_picker.picked_files() {
             // FIXME this `update` method does not get called immediately after picking a file, instead update gets
             //       called when the user moves the mouse or interacts with the window again.
-            self.open_file(ctx, picked_file);
+            let open_as = self.state().open_as.take();
+            // each file is opened in its own tab
+            for picked_file in picked_files {
+                self.open_file(ctx, picked_file, open_as);
+            }
         }
     }
 }
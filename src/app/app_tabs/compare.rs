@@ -0,0 +1,99 @@
+use crate::app::tabs::{Tab, TabKey};
+use crate::app::MessageSource;
+use crate::context::TabContext;
+use crate::documents::comparison::ImageComparison;
+use crate::file_picker::Picker;
+use egui::{Ui, WidgetText};
+use egui_i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Compares two image files.
+///
+/// Only the paths are persisted, the images are loaded again when the application is restarted.
+#[derive(Default, Deserialize, Serialize)]
+pub struct CompareTab {
+    first: Option<PathBuf>,
+    second: Option<PathBuf>,
+
+    #[serde(skip)]
+    first_picker: Picker,
+    #[serde(skip)]
+    second_picker: Picker,
+    #[serde(skip)]
+    comparison: Option<ImageComparison>,
+}
+
+impl Tab for CompareTab {
+    type Context = TabContext;
+
+    fn label(&self) -> WidgetText {
+        let file_name = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .and_then(|path| path.file_name())
+                .map(|file_name| file_name.to_string_lossy().to_string())
+        };
+
+        match (file_name(&self.first), file_name(&self.second)) {
+            (Some(first), Some(second)) => WidgetText::from(format!("{} ↔ {}", first, second)),
+            _ => WidgetText::from(tr!("comparison-tab-label")),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, tab_key: &TabKey, context: &mut Self::Context) {
+        let mut changed = false;
+        if let Ok(path) = self.first_picker.picked() {
+            self.first = Some(path);
+            changed = true;
+        }
+        if let Ok(path) = self.second_picker.picked() {
+            self.second = Some(path);
+            changed = true;
+        }
+        if changed {
+            self.comparison = None;
+        }
+
+        ui.horizontal(|ui| {
+            Self::path_ui(ui, tr!("comparison-first"), &self.first, &mut self.first_picker);
+            ui.separator();
+            Self::path_ui(ui, tr!("comparison-second"), &self.second, &mut self.second_picker);
+        });
+        ui.separator();
+
+        let (Some(first), Some(second)) = (&self.first, &self.second) else {
+            ui.label(tr!("comparison-choose-images"));
+            return;
+        };
+
+        let comparison = self.comparison.get_or_insert_with(|| {
+            let settings = context.config.lock().unwrap().image_decoder.clone();
+            ImageComparison::load(
+                first.clone(),
+                second.clone(),
+                ui.ctx(),
+                MessageSource::Tab(*tab_key),
+                context.sender.clone(),
+                settings,
+            )
+        });
+
+        comparison.ui(ui);
+    }
+}
+
+impl CompareTab {
+    fn path_ui(ui: &mut Ui, label: String, path: &Option<PathBuf>, picker: &mut Picker) {
+        ui.label(label);
+        match path {
+            Some(path) => ui.monospace(path.display().to_string()),
+            None => ui.weak(tr!("comparison-no-image")),
+        };
+        if ui
+            .add_enabled(!picker.is_picking(), egui::Button::new(tr!("comparison-choose")))
+            .clicked()
+        {
+            picker.pick_file();
+        }
+    }
+}
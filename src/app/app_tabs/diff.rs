@@ -0,0 +1,190 @@
+use crate::app::tabs::{Tab, TabKey};
+use crate::app::{AppMessage, MessageSource};
+use crate::context::TabContext;
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::text_diff::TextDiffView;
+use crate::documents::{DocumentKey, DocumentKind};
+use egui::{Ui, WidgetText};
+use egui_i18n::tr;
+use log::error;
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One side of a diff.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum DiffSource {
+    /// the content of an open text document, including any changes that have not been saved.
+    Document(PathBuf),
+    /// the file on disk.
+    Disk(PathBuf),
+}
+
+impl DiffSource {
+    fn label(&self) -> String {
+        match self {
+            DiffSource::Document(path) => file_name(path),
+            DiffSource::Disk(path) => format!("{} ({})", file_name(path), tr!("diff-source-disk")),
+        }
+    }
+
+    fn text<'a>(
+        &self,
+        documents: &'a SlotMap<DocumentKey, DocumentKind>,
+        disk_contents: &'a HashMap<PathBuf, DiskContent>,
+    ) -> Option<&'a str> {
+        match self {
+            DiffSource::Document(path) => documents
+                .values()
+                .find_map(|document_kind| match document_kind {
+                    DocumentKind::TextDocument(document) if document.path == *path => document.text(),
+                    DocumentKind::MarkdownDocument(document) if document.path == *path => document.text(),
+                    DocumentKind::StructuredDocument(document) if document.path == *path => document.text(),
+                    _ => None,
+                }),
+            DiffSource::Disk(path) => disk_contents
+                .get(path)
+                .and_then(DocumentContent::content)
+                .map(String::as_str),
+        }
+    }
+}
+
+/// `Err` if the file could not be read.
+type DiskContent = DocumentContent<String, std::io::Error>;
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(|| path.display().to_string(), |file_name| file_name.to_string_lossy().to_string())
+}
+
+/// Compares two text-based documents, or a document and a file on disk.
+///
+/// Only the sources are persisted, files on disk are read again when the application is restarted, or when reloaded.
+#[derive(Default, Deserialize, Serialize)]
+pub struct DiffTab {
+    old: Option<DiffSource>,
+    new: Option<DiffSource>,
+
+    /// files are loaded in the background, like documents.
+    #[serde(skip)]
+    disk_contents: HashMap<PathBuf, DiskContent>,
+    #[serde(skip)]
+    view: TextDiffView,
+}
+
+impl Tab for DiffTab {
+    type Context = TabContext;
+
+    fn label(&self) -> WidgetText {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => WidgetText::from(format!("{} ↔ {}", old.label(), new.label())),
+            _ => WidgetText::from(tr!("diff-tab-label")),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, tab_key: &TabKey, context: &mut Self::Context) {
+        let documents = context.documents.lock().unwrap();
+
+        let mut text_document_paths: Vec<&PathBuf> = documents
+            .values()
+            .filter_map(|document_kind| match document_kind {
+                DocumentKind::TextDocument(document) => Some(&document.path),
+                DocumentKind::MarkdownDocument(document) => Some(&document.path),
+                DocumentKind::StructuredDocument(document) => Some(&document.path),
+                _ => None,
+            })
+            .collect();
+        text_document_paths.sort();
+        text_document_paths.dedup();
+
+        ui.horizontal(|ui| {
+            Self::source_ui(ui, "old", tr!("diff-old"), &mut self.old, &text_document_paths);
+            ui.separator();
+            Self::source_ui(ui, "new", tr!("diff-new"), &mut self.new, &text_document_paths);
+            ui.separator();
+            if ui.button(tr!("diff-reload")).clicked() {
+                self.disk_contents.clear();
+            }
+        });
+        ui.separator();
+
+        let (Some(old), Some(new)) = (&self.old, &self.new) else {
+            ui.label(tr!("diff-choose-sources"));
+            return;
+        };
+
+        // files are read once, and cached until reloaded.
+        for source in [old, new] {
+            if let DiffSource::Disk(path) = source {
+                let disk_content = self.disk_contents.entry(path.clone()).or_insert_with(|| {
+                    let message = (MessageSource::Tab(*tab_key), AppMessage::Refresh);
+                    DocumentContent::load(path.clone(), ui.ctx(), message, context.sender.clone(), |path, _ctx| {
+                        files::read_to_string(&path)
+                            .inspect_err(|cause| error!("Failed to read file. path: {:?}, cause: {}", path, cause))
+                    })
+                });
+                disk_content.update();
+            }
+        }
+
+        let loading = [old, new].into_iter().any(|source| match source {
+            DiffSource::Disk(path) => self
+                .disk_contents
+                .get(path)
+                .is_some_and(|disk_content| disk_content.content().is_none() && !disk_content.is_error()),
+            DiffSource::Document(_) => false,
+        });
+        if loading {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+            return;
+        }
+
+        let old_text = old.text(&documents, &self.disk_contents);
+        let new_text = new.text(&documents, &self.disk_contents);
+
+        match (old_text, new_text) {
+            (Some(old_text), Some(new_text)) => {
+                self.view.update(old_text, new_text);
+                self.view.ui(ui);
+            }
+            _ => {
+                for (source, text) in [(old, old_text), (new, new_text)] {
+                    if text.is_none() {
+                        ui.label(format!("{} {}", tr!("diff-source-unavailable"), source.label()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl DiffTab {
+    fn source_ui(ui: &mut Ui, id_salt: &str, label: String, source: &mut Option<DiffSource>, paths: &[&PathBuf]) {
+        ui.label(label);
+
+        let selected_text = source
+            .as_ref()
+            .map_or_else(|| tr!("diff-choose"), DiffSource::label);
+
+        egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for path in paths {
+                    let document = DiffSource::Document((*path).clone());
+                    let label = document.label();
+                    ui.selectable_value(source, Some(document), label);
+
+                    // documents that have not been saved yet have no file
+                    if path.exists() {
+                        let disk = DiffSource::Disk((*path).clone());
+                        let label = disk.label();
+                        ui.selectable_value(source, Some(disk), label);
+                    }
+                }
+            });
+    }
+}
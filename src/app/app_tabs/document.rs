@@ -1,7 +1,10 @@
+use crate::app::recent_files::ClosedTab;
 use crate::app::tabs::{Tab, TabKey};
+use crate::app::{AppMessage, MessageSource};
 use crate::context::TabContext;
-use crate::documents::{DocumentContext, DocumentKey, DocumentKind};
+use crate::documents::{files, DocumentContext, DocumentKey, DocumentKind, OpenAs};
 use egui::{Ui, WidgetText};
+use egui_i18n::tr;
 use log::debug;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -12,6 +15,9 @@ pub struct DocumentTab {
     title: String,
     pub path: PathBuf,
     pub document_key: DocumentKey,
+    /// `None` when the document kind is chosen using the file's extension.
+    #[serde(default)]
+    pub open_as: Option<OpenAs>,
 }
 
 impl Tab for DocumentTab {
@@ -39,6 +45,11 @@ impl Tab for DocumentTab {
         match document_kind {
             DocumentKind::TextDocument(document) => document.ui(ui, &mut document_context),
             DocumentKind::ImageDocument(document) => document.ui(ui, &mut document_context),
+            DocumentKind::AnimationDocument(document) => document.ui(ui, &mut document_context),
+            DocumentKind::MarkdownDocument(document) => document.ui(ui, &mut document_context),
+            DocumentKind::StructuredDocument(document) => document.ui(ui, &mut document_context),
+            DocumentKind::TableDocument(document) => document.ui(ui, &mut document_context),
+            DocumentKind::HexDocument(document) => document.ui(ui, &mut document_context),
         }
     }
 
@@ -46,16 +57,55 @@ impl Tab for DocumentTab {
         debug!("removing document. key: {:?}", self.document_key);
         app.documents.lock().unwrap().remove(self.document_key);
 
+        // memory files cannot be opened again once closed, so their bytes are released instead.
+        if files::is_memory_file(&self.path) {
+            files::remove_memory_file(&self.path);
+        } else {
+            app.config.lock().unwrap().recently_closed.add(ClosedTab {
+                title: self.title.clone(),
+                path: self.path.clone(),
+                open_as: self.open_as,
+            });
+        }
+
         OnCloseResponse::Close
     }
+
+    fn context_menu(&mut self, ui: &mut Ui, tab_key: &TabKey, context: &mut Self::Context) {
+        let modified = context
+            .documents
+            .lock()
+            .unwrap()
+            .get(self.document_key)
+            .is_some_and(DocumentKind::is_modified);
+        if modified {
+            // reopening would discard the changes
+            ui.add_enabled(false, egui::Button::new(tr!("tab-context-menu-reopen-as")))
+                .on_disabled_hover_text(tr!("tab-context-menu-reopen-as-modified"));
+            return;
+        }
+
+        ui.menu_button(tr!("tab-context-menu-reopen-as"), |ui| {
+            for open_as in OpenAs::ALL {
+                if ui.button(open_as.label()).clicked() {
+                    context
+                        .sender
+                        .send((MessageSource::Tab(*tab_key), AppMessage::ReopenAs(open_as)))
+                        .unwrap();
+                    ui.close();
+                }
+            }
+        });
+    }
 }
 
 impl DocumentTab {
-    pub fn new(title: String, path: PathBuf, document_key: DocumentKey) -> Self {
+    pub fn new(title: String, path: PathBuf, document_key: DocumentKey, open_as: Option<OpenAs>) -> Self {
         Self {
             title,
             path,
             document_key,
+            open_as,
         }
     }
 }
@@ -1,7 +1,13 @@
+use crate::app::app_tabs::new::KindChoice;
+use crate::app::recent_files::{recent_files_ui, RecentlyClosed};
 use crate::app::tabs::{Tab, TabKey};
-use egui::{Checkbox, FontFamily, RichText, Ui, WidgetText};
+use crate::app::workspaces::Workspaces;
+use crate::app::{AppMessage, MessageSource};
+use egui::{Checkbox, FontFamily, RichText, TextEdit, Ui, WidgetText};
 //use egui_flex::{item, Flex, FlexAlign, FlexDirection, FlexItem, FlexJustify};
 use crate::context::TabContext;
+use crate::documents::image::decoder::DecoderStrategy;
+use crate::documents::DocumentKind;
 use egui_i18n::tr;
 use egui_material_icons::icons::ICON_HOME;
 use egui_taffy::taffy::prelude::{length, percent};
@@ -12,6 +18,10 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Default, Debug, Deserialize, Serialize)]
 pub struct HomeTab {
     show_on_startup: bool,
+
+    /// the name to save the open document tabs as.
+    #[serde(skip)]
+    workspace_name: String,
 }
 
 impl Tab for HomeTab {
@@ -21,12 +31,20 @@ impl Tab for HomeTab {
         egui::widget_text::WidgetText::from(tr!("home-tab-label"))
     }
 
-    fn ui(&mut self, ui: &mut Ui, _tab_key: &TabKey, context: &mut Self::Context) {
+    fn ui(&mut self, ui: &mut Ui, tab_key: &TabKey, context: &mut Self::Context) {
         ui.ctx().style_mut(|style| {
             // if this is not done, text in labels/checkboxes/etc wraps
             style.wrap_mode = Some(egui::TextWrapMode::Extend);
         });
 
+        // all the actions are performed by the app.
+        let send = |message: AppMessage| {
+            context
+                .sender
+                .send((MessageSource::Tab(*tab_key), message))
+                .unwrap();
+        };
+
         let default_style = || Style {
             padding: length(8.),
             gap: length(8.),
@@ -64,12 +82,175 @@ impl Tab for HomeTab {
                     );
                 });
 
+                tui.ui(|ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button(tr!("home-action-new-text")).clicked() {
+                            send(AppMessage::NewDocument(KindChoice::Text));
+                        }
+                        if ui.button(tr!("home-action-new-image")).clicked() {
+                            send(AppMessage::NewDocument(KindChoice::Image));
+                        }
+                        if ui.button(tr!("home-action-open")).clicked() {
+                            send(AppMessage::PickFiles);
+                        }
+                    });
+                });
+
+                tui.style(Style {
+                    flex_direction: taffy::FlexDirection::Row,
+                    align_items: Some(taffy::AlignItems::Start),
+                    ..default_style()
+                })
+                .add(|tui| {
+                    tui.ui(|ui| {
+                        ui.vertical(|ui| {
+                            ui.heading(tr!("recent-files-heading"));
+                            let open_recent = recent_files_ui(ui, &mut context.config.lock().unwrap().recent_files);
+                            if let Some(recent_file) = open_recent {
+                                send(AppMessage::OpenFile(recent_file.path, recent_file.open_as));
+                            }
+                        });
+                    });
+
+                    tui.ui(|ui| {
+                        ui.vertical(|ui| {
+                            ui.heading(tr!("home-recently-closed-heading"));
+                            recently_closed_ui(ui, &context.config.lock().unwrap().recently_closed, &send);
+                        });
+                    });
+
+                    // opening a workspace replaces the documents, which would discard the changes
+                    let modified = context
+                        .documents
+                        .lock()
+                        .unwrap()
+                        .values()
+                        .any(DocumentKind::is_modified);
+
+                    tui.ui(|ui| {
+                        ui.vertical(|ui| {
+                            ui.heading(tr!("home-workspaces-heading"));
+                            workspaces_ui(
+                                ui,
+                                &mut context.config.lock().unwrap().workspaces,
+                                &mut self.workspace_name,
+                                modified,
+                                &send,
+                            );
+                        });
+                    });
+                });
+
                 tui.ui(|ui| {
                     ui.add(Checkbox::new(
                         &mut context.config.lock().unwrap().show_home_tab_on_startup,
                         tr!("home-tab-show-on-startup"),
                     ));
                 });
+
+                tui.ui(|ui| {
+                    let mut config = context.config.lock().unwrap();
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("home-image-decoder"))
+                            .on_hover_text(tr!("home-image-decoder-hint"));
+                        egui::ComboBox::from_id_salt(ui.id().with("image_decoder"))
+                            .selected_text(decoder_strategy_label(config.image_decoder.strategy))
+                            .show_ui(ui, |ui| {
+                                for strategy in DecoderStrategy::ALL {
+                                    ui.selectable_value(
+                                        &mut config.image_decoder.strategy,
+                                        strategy,
+                                        decoder_strategy_label(strategy),
+                                    );
+                                }
+                            });
+                    });
+
+                    let mut max_length = config.recent_files.max_length();
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("recent-files-max-length"));
+                        if ui
+                            .add(egui::DragValue::new(&mut max_length).range(1..=50))
+                            .changed()
+                        {
+                            config.recent_files.set_max_length(max_length);
+                        }
+                    });
+                });
             });
     }
 }
+
+fn decoder_strategy_label(strategy: DecoderStrategy) -> String {
+    match strategy {
+        DecoderStrategy::ImageCrate => tr!("home-image-decoder-image-crate"),
+        DecoderStrategy::EguiExtras => tr!("home-image-decoder-egui-extras"),
+    }
+}
+
+fn recently_closed_ui(ui: &mut Ui, recently_closed: &RecentlyClosed, send: &impl Fn(AppMessage)) {
+    if recently_closed.is_empty() {
+        ui.label(tr!("home-recently-closed-empty"));
+        return;
+    }
+
+    for closed_tab in recently_closed.iter() {
+        if ui
+            .button(&closed_tab.title)
+            .on_hover_text(closed_tab.path.display().to_string())
+            .clicked()
+        {
+            send(AppMessage::OpenFile(closed_tab.path.clone(), closed_tab.open_as));
+        }
+    }
+}
+
+fn workspaces_ui(
+    ui: &mut Ui,
+    workspaces: &mut Workspaces,
+    workspace_name: &mut String,
+    modified: bool,
+    send: &impl Fn(AppMessage),
+) {
+    if workspaces.is_empty() {
+        ui.label(tr!("home-workspaces-empty"));
+    }
+
+    let mut removed = None;
+    for workspace in workspaces.iter() {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!modified, egui::Button::new(&workspace.name))
+                .on_hover_text(format!("{} {}", workspace.files.len(), tr!("home-workspaces-files")))
+                .on_disabled_hover_text(tr!("home-workspaces-modified"))
+                .clicked()
+            {
+                send(AppMessage::OpenWorkspace(workspace.name.clone()));
+            }
+            if ui
+                .small_button("✖")
+                .on_hover_text(tr!("home-workspaces-remove"))
+                .clicked()
+            {
+                removed = Some(workspace.name.clone());
+            }
+        });
+    }
+    if let Some(name) = removed {
+        workspaces.remove(&name);
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.add(TextEdit::singleline(workspace_name).hint_text(tr!("home-workspaces-name-hint")).desired_width(120.0));
+        let name = workspace_name.trim();
+        if ui
+            .add_enabled(!name.is_empty(), egui::Button::new(tr!("home-workspaces-save")))
+            .on_hover_text(tr!("home-workspaces-save-hint"))
+            .clicked()
+        {
+            send(AppMessage::SaveWorkspace(name.to_string()));
+            workspace_name.clear();
+        }
+    });
+}
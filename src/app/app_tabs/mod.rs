@@ -1,3 +1,5 @@
+use crate::app::app_tabs::compare::CompareTab;
+use crate::app::app_tabs::diff::DiffTab;
 use crate::app::app_tabs::document::DocumentTab;
 use crate::app::app_tabs::home::HomeTab;
 use crate::app::app_tabs::new::NewTab;
@@ -7,6 +9,8 @@ use egui::{Ui, WidgetText};
 use egui_dock::tab_viewer::OnCloseResponse;
 use serde::{Deserialize, Serialize};
 
+pub mod compare;
+pub mod diff;
 pub mod document;
 pub mod home;
 pub mod new;
@@ -16,6 +20,8 @@ pub enum TabKind {
     Home(HomeTab),
     Document(DocumentTab),
     New(NewTab),
+    Compare(CompareTab),
+    Diff(DiffTab),
 }
 
 impl Tab for TabKind {
@@ -26,6 +32,8 @@ impl Tab for TabKind {
             TabKind::Home(tab) => tab.label(),
             TabKind::Document(tab) => tab.label(),
             TabKind::New(tab) => tab.label(),
+            TabKind::Compare(tab) => tab.label(),
+            TabKind::Diff(tab) => tab.label(),
         }
     }
 
@@ -34,6 +42,8 @@ impl Tab for TabKind {
             TabKind::Home(tab) => tab.ui(ui, tab_key, context),
             TabKind::Document(tab) => tab.ui(ui, tab_key, context),
             TabKind::New(tab) => tab.ui(ui, tab_key, context),
+            TabKind::Compare(tab) => tab.ui(ui, tab_key, context),
+            TabKind::Diff(tab) => tab.ui(ui, tab_key, context),
         }
     }
 
@@ -42,6 +52,18 @@ impl Tab for TabKind {
             TabKind::Home(tab) => tab.on_close(tab_key, context),
             TabKind::Document(tab) => tab.on_close(tab_key, context),
             TabKind::New(tab) => tab.on_close(tab_key, context),
+            TabKind::Compare(tab) => tab.on_close(tab_key, context),
+            TabKind::Diff(tab) => tab.on_close(tab_key, context),
+        }
+    }
+
+    fn context_menu(&mut self, ui: &mut Ui, tab_key: &TabKey, context: &mut TabContext) {
+        match self {
+            TabKind::Home(tab) => tab.context_menu(ui, tab_key, context),
+            TabKind::Document(tab) => tab.context_menu(ui, tab_key, context),
+            TabKind::New(tab) => tab.context_menu(ui, tab_key, context),
+            TabKind::Compare(tab) => tab.context_menu(ui, tab_key, context),
+            TabKind::Diff(tab) => tab.context_menu(ui, tab_key, context),
         }
     }
 }
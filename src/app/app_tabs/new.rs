@@ -1,13 +1,14 @@
 use crate::app::tabs::{Tab, TabKey};
-use egui::{Button, Response, RichText, TextEdit, Ui, Widget, WidgetText};
+use egui::{Button, DragValue, Response, RichText, TextEdit, Ui, Widget, WidgetText};
 use egui_i18n::{tr, translate_fluent};
 use egui_taffy::taffy::prelude::{auto, fit_content, fr, length, percent, span};
 use egui_taffy::taffy::{AlignContent, AlignItems, AlignSelf, Display, FlexDirection, Style};
 use egui_taffy::{taffy, tui, Tui, TuiBuilderLogic, TuiContainerResponse};
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use log::trace;
-use validator::{Validate, ValidationErrors};
+use validator::{Validate, ValidationError, ValidationErrors};
 // FIXME dependency on AppMessage and AppMessageSender here seems wrong.
 //       Feels like App should depend on new tab, new tab should not depend on App.
 use crate::app::{AppMessage, AppMessageSender, DocumentArgs, MessageSource};
@@ -35,7 +36,44 @@ pub enum KindChoice {
     Image,
 }
 
-#[derive(Clone, Debug, Default, Validate, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ImageFormatChoice {
+    Bmp,
+    Png,
+    Jpeg,
+}
+
+impl ImageFormatChoice {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormatChoice::Bmp => "bmp",
+            ImageFormatChoice::Png => "png",
+            ImageFormatChoice::Jpeg => "jpg",
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ImageFormatChoice::Bmp => tr!("form-new-image-format-bmp"),
+            ImageFormatChoice::Png => tr!("form-new-image-format-png"),
+            ImageFormatChoice::Jpeg => tr!("form-new-image-format-jpeg"),
+        }
+    }
+}
+
+/// Options used when the kind is `KindChoice::Image`.
+#[derive(Clone, Debug)]
+pub struct NewImageOptions {
+    pub width: u32,
+    pub height: u32,
+    /// unmultiplied sRGBA
+    pub background: [u8; 4],
+    pub format: ImageFormatChoice,
+}
+
+#[derive(Clone, Debug, Validate, Deserialize, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+#[validate(schema(function = "validate_image_fields"))]
 struct NewTabForm {
     #[validate(length(min = 1, code = "form-new-name-error-length"))]
     name: String,
@@ -45,6 +83,64 @@ struct NewTabForm {
 
     #[validate(required(code = "form-common-error-required"))]
     directory: Option<PathBuf>,
+
+    //
+    // image fields, only shown and used when the kind is `KindChoice::Image`, see `validate_image_fields`
+    //
+
+    image_width: u32,
+
+    image_height: u32,
+
+    image_background: [u8; 4],
+
+    image_format: ImageFormatChoice,
+}
+
+const IMAGE_DIMENSIONS: RangeInclusive<u32> = 1..=16384;
+
+/// the image fields are hidden for other kinds, so they are only validated for images, otherwise invalid hidden
+/// fields would prevent submitting the form.
+fn validate_image_fields(form: &NewTabForm) -> Result<(), ValidationError> {
+    if form.kind != Some(KindChoice::Image) {
+        return Ok(());
+    }
+
+    if IMAGE_DIMENSIONS.contains(&form.image_width) && IMAGE_DIMENSIONS.contains(&form.image_height) {
+        return Ok(());
+    }
+
+    let mut error = ValidationError::new("form-new-image-error-dimension");
+    error.add_param("min".into(), IMAGE_DIMENSIONS.start());
+    error.add_param("max".into(), IMAGE_DIMENSIONS.end());
+    Err(error)
+}
+
+impl Default for NewTabForm {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            kind: None,
+            directory: None,
+            image_width: 100,
+            image_height: 100,
+            image_background: [0xff, 0xff, 0xff, 0xff],
+            image_format: ImageFormatChoice::Bmp,
+        }
+    }
+}
+
+impl NewTab {
+    /// `kind` is `None` to let the user choose.
+    pub fn new(kind: Option<KindChoice>) -> Self {
+        Self {
+            fields: NewTabForm {
+                kind,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
 }
 
 impl Tab for NewTab {
@@ -252,6 +348,10 @@ impl Tab for NewTab {
                             );
                         });
                         Self::field_error(&validation_errors, default_style, tui, "kind");
+
+                        if self.fields.kind == Some(KindChoice::Image) {
+                            self.image_fields_ui(&validation_errors, default_style, tui);
+                        }
                     });
                 });
 
@@ -278,6 +378,12 @@ impl NewTab {
             name: self.fields.name.clone(),
             directory: self.fields.directory.as_ref().unwrap().clone(),
             kind: self.fields.kind.as_ref().unwrap().clone(),
+            image_options: NewImageOptions {
+                width: self.fields.image_width,
+                height: self.fields.image_height,
+                background: self.fields.image_background,
+                format: self.fields.image_format,
+            },
         };
 
         sender
@@ -288,6 +394,89 @@ impl NewTab {
             .unwrap()
     }
 
+    fn image_fields_ui(
+        &mut self,
+        validation_errors: &Result<(), ValidationErrors>,
+        default_style: fn() -> Style,
+        tui: &mut Tui,
+    ) {
+        //
+        // Width and height fields
+        //
+
+        tui.style(Style { ..default_style() }).add(|tui| {
+            tui.label(tr!("form-new-image-width"));
+        });
+        tui.style(Style {
+            flex_grow: 1.0,
+            ..default_style()
+        })
+        .ui_add(DragValue::new(&mut self.fields.image_width).range(IMAGE_DIMENSIONS));
+
+        tui.style(Style { ..default_style() }).add(|tui| {
+            tui.label(tr!("form-new-image-height"));
+        });
+        tui.style(Style {
+            flex_grow: 1.0,
+            ..default_style()
+        })
+        .ui_add(DragValue::new(&mut self.fields.image_height).range(IMAGE_DIMENSIONS));
+
+        // errors from `validate_image_fields`, which is not specific to a field
+        Self::field_error(validation_errors, default_style, tui, "__all__");
+
+        //
+        // Background field
+        //
+
+        tui.style(Style { ..default_style() }).add(|tui| {
+            tui.label(tr!("form-new-image-background"));
+        });
+        tui.style(Style {
+            flex_grow: 1.0,
+            ..default_style()
+        })
+        .add(|tui| {
+            tui.ui(|ui| {
+                ui.color_edit_button_srgba_unmultiplied(&mut self.fields.image_background);
+            });
+        });
+
+        //
+        // Format field
+        //
+
+        tui.style(Style { ..default_style() }).add(|tui| {
+            tui.label(tr!("form-new-image-format"));
+        });
+        tui.style(Style {
+            flex_grow: 1.0,
+            ..default_style()
+        })
+        .add(|tui| {
+            tui.ui(|ui| {
+                egui::ComboBox::from_id_salt(ui.id().with("image_format"))
+                    .width(ui.available_width())
+                    .selected_text(self.fields.image_format.label())
+                    .show_ui(ui, |ui| {
+                        for format in [ImageFormatChoice::Bmp, ImageFormatChoice::Png, ImageFormatChoice::Jpeg] {
+                            ui.selectable_value(&mut self.fields.image_format, format, format.label());
+                        }
+                    });
+            });
+        });
+
+        if self.fields.image_format == ImageFormatChoice::Jpeg && self.fields.image_background[3] != 0xff {
+            tui.style(Style {
+                grid_column: span(2),
+                ..default_style()
+            })
+            .add(|tui| {
+                tui.label(tr!("form-new-image-jpeg-alpha-warning"));
+            });
+        }
+    }
+
     fn field_error(
         validation_errors: &Result<(), ValidationErrors>,
         default_style: fn() -> Style,
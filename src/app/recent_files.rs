@@ -0,0 +1,206 @@
+use crate::documents::OpenAs;
+use egui::Ui;
+use egui_i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// the number of unpinned files that are kept, unless changed by the user.
+const DEFAULT_MAX_LENGTH: usize = 10;
+/// the number of closed tabs that are kept.
+const MAX_RECENTLY_CLOSED: usize = 10;
+
+/// A most-recently-used list of opened files, persisted in the `Config`.
+///
+/// Pinned files are listed first and are never removed when the list is too long.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct RecentFiles {
+    /// most recently opened first.
+    entries: Vec<RecentFile>,
+    /// the maximum number of unpinned files.
+    max_length: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    /// the document kind the file was last opened as, `None` when chosen using the file's extension.
+    pub open_as: Option<OpenAs>,
+    pub pinned: bool,
+}
+
+impl Default for RecentFiles {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            max_length: DEFAULT_MAX_LENGTH,
+        }
+    }
+}
+
+impl RecentFiles {
+    /// moves the file to the top of the list, keeping it pinned if it was already pinned.
+    pub fn add(&mut self, path: PathBuf, open_as: Option<OpenAs>) {
+        let pinned = self
+            .entries
+            .iter()
+            .any(|entry| entry.path == path && entry.pinned);
+
+        self.remove(&path);
+        self.entries.insert(0, RecentFile {
+            path,
+            open_as,
+            pinned,
+        });
+
+        self.truncate();
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.retain(|entry| entry.path != path);
+    }
+
+    pub fn set_pinned(&mut self, path: &Path, pinned: bool) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.path == path) {
+            entry.pinned = pinned;
+        }
+        self.truncate();
+    }
+
+    /// removes files that have been deleted or moved since they were opened, pinned files are also removed.
+    pub fn remove_missing(&mut self) {
+        self.entries.retain(|entry| entry.path.exists());
+    }
+
+    /// removes all the files that are not pinned.
+    pub fn clear(&mut self) {
+        self.entries.retain(|entry| entry.pinned);
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    pub fn set_max_length(&mut self, max_length: usize) {
+        self.max_length = max_length;
+        self.truncate();
+    }
+
+    /// pinned files first, then the most recently opened.
+    pub fn entries(&self) -> Vec<RecentFile> {
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = self
+            .entries
+            .iter()
+            .cloned()
+            .partition(|entry| entry.pinned);
+
+        pinned.into_iter().chain(unpinned).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn truncate(&mut self) {
+        let max_length = self.max_length;
+        let mut unpinned = 0;
+        self.entries.retain(|entry| {
+            if entry.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= max_length
+        });
+    }
+}
+
+/// Document tabs that were closed, so they can be opened again, most recently closed first.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct RecentlyClosed {
+    tabs: Vec<ClosedTab>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClosedTab {
+    pub title: String,
+    pub path: PathBuf,
+    /// `None` when the document kind is chosen using the file's extension.
+    pub open_as: Option<OpenAs>,
+}
+
+impl RecentlyClosed {
+    pub fn add(&mut self, closed_tab: ClosedTab) {
+        self.remove(&closed_tab.path);
+        self.tabs.insert(0, closed_tab);
+        self.tabs.truncate(MAX_RECENTLY_CLOSED);
+    }
+
+    /// called when a file is opened, as it's no longer closed.
+    pub fn remove(&mut self, path: &Path) {
+        self.tabs.retain(|closed_tab| closed_tab.path != path);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ClosedTab> {
+        self.tabs.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+}
+
+/// used for both the 'Open Recent' menu and the home tab, pinning and removing are applied immediately.
+///
+/// returns the file to open, if one was clicked.
+pub fn recent_files_ui(ui: &mut Ui, recent_files: &mut RecentFiles) -> Option<RecentFile> {
+    if recent_files.is_empty() {
+        ui.label(tr!("recent-files-empty"));
+        return None;
+    }
+
+    let mut clicked = None;
+    for entry in recent_files.entries() {
+        ui.horizontal(|ui| {
+            let exists = entry.path.exists();
+            let name = entry
+                .path
+                .file_name()
+                .map_or_else(|| entry.path.display().to_string(), |name| name.to_string_lossy().to_string());
+
+            let response = ui
+                .add_enabled(exists, egui::Button::new(name))
+                .on_hover_text(entry.path.display().to_string())
+                .on_disabled_hover_text(format!("{} {}", tr!("recent-files-missing"), entry.path.display()));
+            if response.clicked() {
+                clicked = Some(entry.clone());
+            }
+
+            if ui
+                .selectable_label(entry.pinned, tr!("recent-files-pin"))
+                .clicked()
+            {
+                recent_files.set_pinned(&entry.path, !entry.pinned);
+            }
+            if ui
+                .small_button("✖")
+                .on_hover_text(tr!("recent-files-remove"))
+                .clicked()
+            {
+                recent_files.remove(&entry.path);
+            }
+        });
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        if ui.button(tr!("recent-files-remove-missing")).clicked() {
+            recent_files.remove_missing();
+        }
+        if ui.button(tr!("recent-files-clear")).clicked() {
+            recent_files.clear();
+        }
+    });
+
+    clicked
+}
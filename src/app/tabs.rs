@@ -1,5 +1,5 @@
 use egui::{Id, Ui, WidgetText};
-use egui_dock::TabViewer;
+use egui_dock::{NodeIndex, SurfaceIndex, TabViewer};
 use egui_dock::tab_viewer::OnCloseResponse;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
@@ -36,6 +36,11 @@ impl<Context, TabKind: Tab<Context = Context>> Tabs<TabKind, Context> {
         id
     }
 
+    /// removes the tab without calling `on_close`.
+    pub fn remove(&mut self, key: &TabKey) -> Option<TabKind> {
+        self.tabs.remove(key)
+    }
+
     pub fn get(&self, key: &TabKey) -> Option<&TabKind> {
         self.tabs.get(key)
     }
@@ -98,6 +103,9 @@ pub trait Tab {
     fn on_close<'a>(&mut self, _tab_key: &TabKey, _app: &mut Self::Context) -> OnCloseResponse {
         OnCloseResponse::Close
     }
+
+    // additional items for the menu shown when the tab is right-clicked.
+    fn context_menu<'a>(&mut self, _ui: &mut Ui, _tab_key: &TabKey, _app: &mut Self::Context) {}
 }
 
 pub struct AppTabViewer<'a, TabContext, TabKind: Tab> {
@@ -124,6 +132,12 @@ impl<'a, TabContext, TabKind: Tab<Context = TabContext>> TabViewer for AppTabVie
         }
     }
 
+    fn context_menu(&mut self, ui: &mut Ui, tab: &mut Self::Tab, _surface: SurfaceIndex, _node: NodeIndex) {
+        if let Some(tab_instance) = self.tabs.tabs.get_mut(tab) {
+            tab_instance.context_menu(ui, tab, self.context);
+        }
+    }
+
     fn on_close(&mut self, tab: &mut Self::Tab) -> OnCloseResponse {
         // FIXME this isn't called when the 'close all' button in the tab bar is used.
         //       reported to maintainer - https://discord.com/channels/900275882684477440/1075333382290026567/1339624259697246348
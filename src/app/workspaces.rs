@@ -0,0 +1,54 @@
+use crate::documents::OpenAs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A named set of files, saved from the open document tabs, opening it replaces the open document tabs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Workspace {
+    pub name: String,
+    /// in the order the tabs were in when the workspace was saved.
+    pub files: Vec<WorkspaceFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WorkspaceFile {
+    pub path: PathBuf,
+    /// `None` when the document kind is chosen using the file's extension.
+    pub open_as: Option<OpenAs>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct Workspaces {
+    /// sorted by name.
+    workspaces: Vec<Workspace>,
+}
+
+impl Workspaces {
+    /// replaces any existing workspace with the same name.
+    pub fn save(&mut self, workspace: Workspace) {
+        self.remove(&workspace.name);
+        self.workspaces.push(workspace);
+        self.workspaces
+            .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.workspaces
+            .retain(|workspace| workspace.name != name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.workspaces
+            .iter()
+            .find(|workspace| workspace.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Workspace> {
+        self.workspaces.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workspaces.is_empty()
+    }
+}
@@ -0,0 +1,142 @@
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: tabbed_document_ui_example [OPTIONS] [FILES]...
+
+Opens the files in tabs, after the tabs from the previous session have been restored.
+
+Options:
+  --new-window       start a new session, which is neither restored nor saved
+  --no-restore       start without the tabs from the previous session
+  --line <N>         move the cursor to line N in text documents
+  --single-instance  open the files in the running instance, if there is one, instead of in a new window
+  -h, --help         show this help
+";
+
+/// The command-line arguments.
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    /// opened in tabs on startup, relative paths are relative to the current directory.
+    pub files: Vec<PathBuf>,
+    /// start a new session, and never forward to a running instance.
+    pub new_window: bool,
+    pub no_restore: bool,
+    pub single_instance: bool,
+    /// 1-based
+    pub line: Option<usize>,
+    pub help: bool,
+}
+
+#[derive(Debug)]
+pub enum ArgsError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidLine(String),
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::UnknownOption(option) => write!(f, "unknown option: {}", option),
+            ArgsError::MissingValue(option) => write!(f, "missing value for option: {}", option),
+            ArgsError::InvalidLine(value) => write!(f, "invalid line number: {}", value),
+        }
+    }
+}
+
+impl Args {
+    /// `args` should not include the program name.
+    pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, ArgsError> {
+        let mut result = Self::default();
+        // after `--`, everything is a file, even if it starts with `-`.
+        let mut only_files = false;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if only_files || !arg.as_encoded_bytes().starts_with(b"-") {
+                result.files.push(PathBuf::from(arg));
+                continue;
+            }
+
+            // only options are converted to text, files are kept as given, they may not be valid unicode.
+            let option = arg.to_string_lossy();
+            match option.as_ref() {
+                "--" => only_files = true,
+                "--new-window" => result.new_window = true,
+                "--no-restore" => result.no_restore = true,
+                "--single-instance" => result.single_instance = true,
+                "--line" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| ArgsError::MissingValue(option.to_string()))?;
+                    let value = value.to_string_lossy();
+                    let line = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|line| *line >= 1)
+                        .ok_or_else(|| ArgsError::InvalidLine(value.to_string()))?;
+                    result.line = Some(line);
+                }
+                "-h" | "--help" => result.help = true,
+                _ => return Err(ArgsError::UnknownOption(option.to_string())),
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn files_and_options() {
+        let args = parse(&["a.txt", "--new-window", "b.txt", "--line", "3"]).unwrap();
+        assert_eq!(args.files, vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        assert!(args.new_window);
+        assert!(!args.no_restore);
+        assert_eq!(args.line, Some(3));
+    }
+
+    #[test]
+    fn everything_after_double_dash_is_a_file() {
+        let args = parse(&["--no-restore", "--", "--new-window", "-h"]).unwrap();
+        assert!(args.no_restore);
+        assert!(!args.new_window);
+        assert!(!args.help);
+        assert_eq!(args.files, vec![PathBuf::from("--new-window"), PathBuf::from("-h")]);
+    }
+
+    #[test]
+    fn line_without_value() {
+        assert!(matches!(parse(&["--line"]), Err(ArgsError::MissingValue(option)) if option == "--line"));
+    }
+
+    #[test]
+    fn invalid_line() {
+        assert!(matches!(parse(&["--line", "0"]), Err(ArgsError::InvalidLine(value)) if value == "0"));
+        assert!(matches!(parse(&["--line", "x"]), Err(ArgsError::InvalidLine(value)) if value == "x"));
+    }
+
+    #[test]
+    fn unknown_option() {
+        assert!(matches!(parse(&["--bogus"]), Err(ArgsError::UnknownOption(option)) if option == "--bogus"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_file() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let file = OsString::from_vec(vec![b'a', 0xff]);
+        let args = Args::parse([file.clone()]).unwrap();
+        assert_eq!(args.files, vec![PathBuf::from(file)]);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::image::decoder::{check_limits, DecodeError, DecoderSettings};
+use crate::documents::image::viewer::ImageViewer;
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::sidebar::sidebar_ui;
+use crate::documents::{DocumentContext, DocumentKey};
+use egui::load::SizedTexture;
+use egui::{ColorImage, Context, TextureHandle, TextureOptions, Ui};
+use egui_i18n::tr;
+use image::codecs::png::PngDecoder;
+use image::metadata::Orientation;
+use image::{AnimationDecoder, DynamicImage, Frame, ImageDecoder, ImageFormat};
+use log::{error, info};
+#[cfg(feature = "formats-gif")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+/// many GIFs specify a delay of zero, or close to zero, browsers use 100ms instead, so do we.
+const MINIMUM_FRAME_DELAY: Duration = Duration::from_millis(20);
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// A sequence of frames, e.g. an animated GIF or APNG.
+pub struct AnimationDocument {
+    pub path: PathBuf,
+
+    loader: DocumentContent<AnimationContent, AnimationLoaderError>,
+    viewer: ImageViewer,
+    playback: Playback,
+}
+
+struct AnimationContent {
+    /// all frames are the same size, the decoders composite each frame onto the previous ones.
+    dimensions: [u32; 2],
+    frames: Vec<AnimationFrame>,
+}
+
+impl AnimationContent {
+    fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+}
+
+struct AnimationFrame {
+    texture_handle: TextureHandle,
+    delay: Duration,
+}
+
+enum AnimationLoaderError {
+    Error,
+}
+
+struct Playback {
+    playing: bool,
+    current: usize,
+    /// the time, from `egui::InputState::time`, that the current frame was first shown, `None` when paused.
+    frame_started: Option<f64>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            current: 0,
+            frame_started: None,
+        }
+    }
+}
+
+impl Playback {
+    fn toggle(&mut self) {
+        self.playing = !self.playing;
+        self.frame_started = None;
+    }
+
+    fn step(&mut self, frame_count: usize, forwards: bool) {
+        self.playing = false;
+        self.frame_started = None;
+        self.current = match forwards {
+            true => (self.current + 1) % frame_count,
+            false => (self.current + frame_count - 1) % frame_count,
+        };
+    }
+
+    /// advances to the next frame(s) when the current frame's delay has elapsed.
+    fn update(&mut self, ctx: &Context, frames: &[AnimationFrame]) {
+        if !self.playing || frames.len() < 2 {
+            return;
+        }
+
+        let now = ctx.input(|input| input.time);
+        let mut frame_started = *self.frame_started.get_or_insert(now);
+
+        loop {
+            let delay = frames[self.current].delay.as_secs_f64();
+            if now - frame_started < delay {
+                let remaining = Duration::from_secs_f64(delay - (now - frame_started));
+                ctx.request_repaint_after(remaining);
+                break;
+            }
+
+            frame_started += delay;
+            self.current = (self.current + 1) % frames.len();
+        }
+
+        self.frame_started = Some(frame_started);
+    }
+}
+
+impl AnimationDocument {
+    pub fn from_path(
+        path: PathBuf,
+        ctx: &Context,
+        document_key: DocumentKey,
+        sender: AppMessageSender,
+        settings: DecoderSettings,
+    ) -> Self {
+        let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let loader = DocumentContent::load(path.clone(), ctx, message, sender, move |path, ctx| {
+            let frames = decode_frames(&path, &settings)
+                .inspect_err(|cause| error!("Failed to decode frames. path: {:?}, cause: {}", path, cause))
+                .map_err(|_| AnimationLoaderError::Error)?;
+
+            let url = Url::from_file_path(path.clone()).unwrap();
+
+            let mut dimensions = [0, 0];
+            let frames: Vec<AnimationFrame> = frames
+                .into_iter()
+                .enumerate()
+                .map(|(index, frame)| {
+                    let delay = Duration::from(frame.delay());
+                    let delay = match delay < MINIMUM_FRAME_DELAY {
+                        true => DEFAULT_FRAME_DELAY,
+                        false => delay,
+                    };
+
+                    let pixels = frame.into_buffer();
+                    dimensions = [pixels.width(), pixels.height()];
+
+                    let size = [pixels.width() as usize, pixels.height() as usize];
+                    let texture_handle = ctx.load_texture(
+                        format!("{}#frame{}", url, index),
+                        ColorImage::from_rgba_unmultiplied(size, pixels.as_raw()),
+                        TextureOptions::default(),
+                    );
+
+                    AnimationFrame { texture_handle, delay }
+                })
+                .collect();
+
+            if frames.is_empty() {
+                error!("No frames. path: {:?}", path);
+                return Err(AnimationLoaderError::Error);
+            }
+
+            info!("Animation loaded. frames: {}", frames.len());
+
+            Ok(AnimationContent { dimensions, frames })
+        });
+
+        Self {
+            path,
+            loader,
+            viewer: ImageViewer::default(),
+            playback: Playback::default(),
+        }
+    }
+
+    pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
+        self.loader.update();
+
+        if let Some(content) = self.loader.content() {
+            self.playback.update(ui.ctx(), &content.frames);
+        }
+
+        let content = self.loader.content();
+        let unknown = || tr!("generic-unknown-value");
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (
+                tr!("image-sidebar-dimensions"),
+                content.map_or_else(unknown, |content| {
+                    format!("{} x {}", content.dimensions[0], content.dimensions[1])
+                }),
+            ),
+            (
+                tr!("animation-sidebar-frames"),
+                content.map_or_else(unknown, |content| content.frames.len().to_string()),
+            ),
+            (
+                tr!("animation-sidebar-duration"),
+                content.map_or_else(unknown, |content| {
+                    format!("{} ms", content.total_duration().as_millis())
+                }),
+            ),
+        ];
+
+        sidebar_ui(ui, rows, |ui| {
+            if let Some(content) = content {
+                Self::playback_ui(ui, &mut self.playback, content);
+            }
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.content_ui(ui);
+        });
+    }
+
+    fn playback_ui(ui: &mut Ui, playback: &mut Playback, content: &AnimationContent) {
+        let frame_count = content.frames.len();
+
+        ui.heading(tr!("animation-playback-heading"));
+
+        ui.horizontal(|ui| {
+            if ui.button(tr!("animation-playback-previous")).clicked() {
+                playback.step(frame_count, false);
+            }
+
+            let play_pause = match playback.playing {
+                true => tr!("animation-playback-pause"),
+                false => tr!("animation-playback-play"),
+            };
+            if ui.button(play_pause).clicked() {
+                playback.toggle();
+            }
+
+            if ui.button(tr!("animation-playback-next")).clicked() {
+                playback.step(frame_count, true);
+            }
+        });
+
+        let mut current = playback.current;
+        let scrubber = ui.add(
+            egui::Slider::new(&mut current, 0..=frame_count - 1).text(tr!("animation-playback-frame")),
+        );
+        if scrubber.changed() {
+            // scrubbing pauses playback, so the chosen frame stays visible.
+            playback.playing = false;
+            playback.frame_started = None;
+            playback.current = current;
+        }
+
+        let delay = content.frames[playback.current].delay;
+        ui.label(format!("{} {} ms", tr!("animation-playback-delay"), delay.as_millis()));
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        if self.loader.is_error() {
+            ui.label(tr!("file-loading-error"));
+        } else if let Some(content) = self.loader.content() {
+            ui.horizontal(|ui| {
+                self.viewer.zoom_controls_ui(ui);
+            });
+
+            let frame = &content.frames[self.playback.current];
+            let texture = SizedTexture::from_handle(&frame.texture_handle);
+
+            egui::Frame::new().show(ui, |ui| {
+                // frames are decoded with the `image` crate, which does not apply any orientation.
+                self.viewer.ui(ui, texture, None, Orientation::NoTransforms);
+            });
+        } else {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+        }
+    }
+}
+
+/// `true` for GIF files with more than one frame, and APNG files.
+///
+/// this is called on the UI thread, so no pixels are decoded, the limits are checked when the frames are loaded.
+pub fn is_animation(path: &Path) -> bool {
+    match ImageFormat::from_path(path) {
+        #[cfg(feature = "formats-gif")]
+        Ok(ImageFormat::Gif) => {
+            // single-frame GIFs are shown as images, so they can be edited.
+            files::open(path)
+                .and_then(has_second_gif_frame)
+                .unwrap_or(false)
+        }
+        Ok(ImageFormat::Png) => {
+            // only the header is read, not the image data.
+            files::open(path)
+                .ok()
+                .and_then(|reader| PngDecoder::new(reader).ok())
+                .and_then(|decoder| decoder.is_apng().ok())
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// reads the block structure of a GIF, skipping the color tables, extensions and image data, until a second image
+/// descriptor is found.
+#[cfg(feature = "formats-gif")]
+fn has_second_gif_frame(mut reader: impl Read) -> std::io::Result<bool> {
+    const EXTENSION_INTRODUCER: u8 = 0x21;
+    const IMAGE_DESCRIPTOR: u8 = 0x2C;
+
+    fn read_u8(reader: &mut impl Read) -> std::io::Result<u8> {
+        let mut byte = [0_u8; 1];
+        reader.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn skip(reader: &mut impl Read, length: u64) -> std::io::Result<()> {
+        let skipped = std::io::copy(&mut reader.by_ref().take(length), &mut std::io::sink())?;
+        if skipped < length {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    /// the size of a color table, from the packed field of the screen or image descriptor.
+    fn color_table_length(packed: u8) -> u64 {
+        match packed & 0x80 {
+            0 => 0,
+            _ => 3 * (1 << ((packed & 0x07) + 1)),
+        }
+    }
+
+    /// data sub-blocks, each one starts with its length, a length of zero ends them.
+    fn skip_sub_blocks(reader: &mut impl Read) -> std::io::Result<()> {
+        loop {
+            match read_u8(reader)? {
+                0 => return Ok(()),
+                length => skip(reader, length as u64)?,
+            }
+        }
+    }
+
+    // header and logical screen descriptor, the packed field is the 5th byte of the descriptor.
+    let mut header = [0_u8; 13];
+    reader.read_exact(&mut header)?;
+    if !header.starts_with(b"GIF") {
+        return Ok(false);
+    }
+    skip(&mut reader, color_table_length(header[10]))?;
+
+    let mut frames = 0;
+    loop {
+        match read_u8(&mut reader)? {
+            EXTENSION_INTRODUCER => {
+                let _label = read_u8(&mut reader)?;
+                skip_sub_blocks(&mut reader)?;
+            }
+            IMAGE_DESCRIPTOR => {
+                frames += 1;
+                if frames > 1 {
+                    return Ok(true);
+                }
+                // position, size and packed field.
+                let mut descriptor = [0_u8; 9];
+                reader.read_exact(&mut descriptor)?;
+                skip(&mut reader, color_table_length(descriptor[8]))?;
+                let _lzw_minimum_code_size = read_u8(&mut reader)?;
+                skip_sub_blocks(&mut reader)?;
+            }
+            // the trailer, or an invalid block
+            _ => return Ok(false),
+        }
+    }
+}
+
+/// the limits are applied to each frame, the memory limit is also applied to all the frames together.
+fn decode_frames(path: &Path, settings: &DecoderSettings) -> Result<Vec<Frame>, DecodeError> {
+    let reader = files::open(path)?;
+
+    let frames = match ImageFormat::from_path(path)? {
+        #[cfg(feature = "formats-gif")]
+        ImageFormat::Gif => {
+            let mut decoder = image::codecs::gif::GifDecoder::new(reader)?;
+            decoder.set_limits(settings.limits())?;
+            decoder.into_frames()
+        }
+        _ => {
+            let decoder = PngDecoder::with_limits(reader, settings.limits())?;
+            match decoder.is_apng()? {
+                true => decoder.apng()?.into_frames(),
+                // a plain PNG is a single frame
+                false => {
+                    let (width, height) = decoder.dimensions();
+                    check_limits(width, height, settings)?;
+                    return Ok(vec![Frame::new(DynamicImage::from_decoder(decoder)?.to_rgba8())]);
+                }
+            }
+        }
+    };
+
+    let mut decoded_frames = vec![];
+    let mut memory = 0_u64;
+    for frame in frames {
+        let frame = frame?;
+        let (width, height) = frame.buffer().dimensions();
+        check_limits(width, height, settings)?;
+
+        // RGBA, 1 byte per channel, the same as `check_limits`.
+        memory += width as u64 * height as u64 * 4;
+        if memory > settings.max_memory {
+            return Err(DecodeError::AnimationTooLarge {
+                frames: decoded_frames.len(),
+            });
+        }
+
+        decoded_frames.push(frame);
+    }
+
+    Ok(decoded_frames)
+}
+
+#[cfg(test)]
+#[cfg(feature = "formats-gif")]
+mod tests {
+    use super::*;
+
+    /// a GIF with the given number of 1x1 frames, each one with a graphic control extension.
+    fn gif(frames: usize) -> Vec<u8> {
+        let mut bytes = b"GIF89a".to_vec();
+        // 1x1 screen, with a global color table of 2 colors
+        bytes.extend([1, 0, 1, 0, 0x80, 0, 0]);
+        bytes.extend([0; 6]);
+        for _ in 0..frames {
+            bytes.extend([0x21, 0xF9, 4, 0, 10, 0, 0, 0]);
+            bytes.extend([0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0]);
+            bytes.extend([2, 2, 0x44, 0x01, 0]);
+        }
+        bytes.push(0x3B);
+        bytes
+    }
+
+    #[test]
+    fn single_frame_gif_is_not_an_animation() {
+        assert!(!has_second_gif_frame(gif(1).as_slice()).unwrap());
+    }
+
+    #[test]
+    fn gif_with_two_frames_is_an_animation() {
+        assert!(has_second_gif_frame(gif(2).as_slice()).unwrap());
+    }
+
+    #[test]
+    fn truncated_gif_is_an_error() {
+        let bytes = gif(2);
+        assert!(has_second_gif_frame(&bytes[..20]).is_err());
+    }
+}
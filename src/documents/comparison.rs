@@ -0,0 +1,340 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::image::decoder::{decode, DecodeError, DecoderSettings};
+use crate::documents::image::tiles::ImageTextures;
+use crate::documents::image::viewer::{ImageViewer, ImageViewerResponse};
+use crate::documents::loader::DocumentContent;
+use egui::{pos2, vec2, Color32, Context, Rect, Stroke, Ui};
+use egui_i18n::tr;
+use image::metadata::Orientation;
+use image::{Rgba, RgbaImage};
+use log::error;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ComparisonMode {
+    #[default]
+    SideBySide,
+    /// the second image is drawn over the first, with an adjustable opacity.
+    OnionSkin,
+    /// the second image is drawn over the first, to the right of an adjustable divider.
+    Swipe,
+    /// changed pixels are highlighted.
+    Difference,
+}
+
+impl ComparisonMode {
+    const ALL: [ComparisonMode; 4] = [
+        ComparisonMode::SideBySide,
+        ComparisonMode::OnionSkin,
+        ComparisonMode::Swipe,
+        ComparisonMode::Difference,
+    ];
+
+    fn label(&self) -> String {
+        match self {
+            ComparisonMode::SideBySide => tr!("comparison-mode-side-by-side"),
+            ComparisonMode::OnionSkin => tr!("comparison-mode-onion-skin"),
+            ComparisonMode::Swipe => tr!("comparison-mode-swipe"),
+            ComparisonMode::Difference => tr!("comparison-mode-difference"),
+        }
+    }
+}
+
+/// A numeric summary of the differences between two images.
+#[derive(Clone, Debug)]
+pub struct DiffSummary {
+    pub changed_pixels: u64,
+    pub total_pixels: u64,
+    /// peak signal-to-noise ratio, in dB, pixels outside the overlapping area count as the maximum error.
+    /// `None` if the images are identical (infinite PSNR), which requires the same dimensions.
+    pub psnr: Option<f64>,
+    pub same_dimensions: bool,
+}
+
+struct ComparedImage {
+    pixels: RgbaImage,
+    textures: ImageTextures,
+}
+
+struct ComparisonContent {
+    first: ComparedImage,
+    second: ComparedImage,
+    difference: ComparedImage,
+    summary: DiffSummary,
+}
+
+#[derive(Debug)]
+enum ComparisonLoaderError {
+    Decode(DecodeError),
+}
+
+/// Compares two image files, the images are loaded in the background.
+pub struct ImageComparison {
+    loader: DocumentContent<ComparisonContent, ComparisonLoaderError>,
+    viewer: ImageViewer,
+    mode: ComparisonMode,
+    /// 0.0 to 1.0, used by `ComparisonMode::OnionSkin`
+    opacity: f32,
+    /// 0.0 to 1.0, the divider position relative to the image width, used by `ComparisonMode::Swipe`
+    swipe: f32,
+}
+
+impl ImageComparison {
+    pub fn load(
+        first: PathBuf,
+        second: PathBuf,
+        ctx: &Context,
+        source: MessageSource,
+        sender: AppMessageSender,
+        settings: DecoderSettings,
+    ) -> Self {
+        let message = (source, AppMessage::Refresh);
+        let max_texture_side = ImageTextures::max_texture_side(ctx);
+        let loader = DocumentContent::load(first, ctx, message, sender, move |first, ctx| {
+            let load = |path: &PathBuf| {
+                decode(path, &settings)
+                    .inspect_err(|cause| error!("Failed to load image. path: {:?}, cause: {}", path, cause))
+                    .map(|image| image.to_rgba8())
+                    .map_err(ComparisonLoaderError::Decode)
+            };
+
+            let first = load(&first)?;
+            let second = load(&second)?;
+
+            let (difference, summary) = compare(&first, &second);
+
+            let upload = |name: &str, pixels: RgbaImage| ComparedImage {
+                textures: ImageTextures::new(ctx, name.to_string(), &pixels, max_texture_side),
+                pixels,
+            };
+
+            Ok(ComparisonContent {
+                first: upload("comparison#first", first),
+                second: upload("comparison#second", second),
+                difference: upload("comparison#difference", difference),
+                summary,
+            })
+        });
+
+        Self {
+            loader,
+            viewer: ImageViewer::default(),
+            mode: ComparisonMode::default(),
+            opacity: 0.5,
+            swipe: 0.5,
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        self.loader.update();
+
+        if let Some(ComparisonLoaderError::Decode(cause)) = self.loader.error() {
+            ui.label(tr!("file-loading-error"));
+            ui.label(cause.to_string());
+            return;
+        }
+
+        let Some(content) = self.loader.content() else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr!("file-loading"));
+            });
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            for mode in ComparisonMode::ALL {
+                ui.selectable_value(&mut self.mode, mode, mode.label());
+            }
+            ui.separator();
+
+            match self.mode {
+                ComparisonMode::OnionSkin => {
+                    ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0).text(tr!("comparison-opacity")));
+                }
+                ComparisonMode::Swipe => {
+                    ui.add(egui::Slider::new(&mut self.swipe, 0.0..=1.0).text(tr!("comparison-swipe")));
+                }
+                ComparisonMode::SideBySide | ComparisonMode::Difference => {}
+            }
+            ui.separator();
+
+            self.viewer.zoom_controls_ui(ui);
+        });
+
+        Self::summary_ui(ui, &content.summary);
+
+        // comparisons use the preview textures only, huge images are compared at reduced resolution.
+        let first = content.first.textures.texture(&content.first.pixels);
+        let second = content.second.textures.texture(&content.second.pixels);
+
+        match self.mode {
+            ComparisonMode::SideBySide => {
+                // the same viewer is used for both, so zoom and pan are synchronized.
+                self.viewer.ui_side_by_side(ui, first, second);
+            }
+            ComparisonMode::OnionSkin => {
+                let response = self.viewer.ui(ui, first, None, Orientation::NoTransforms);
+                let tint = Color32::WHITE.gamma_multiply(self.opacity);
+                paint_overlay(ui, &response, second.id, second_rect(&response, &content.second.pixels), tint, None);
+            }
+            ComparisonMode::Swipe => {
+                let response = self.viewer.ui(ui, first, None, Orientation::NoTransforms);
+                let image_rect = response.image_rect;
+                let divider = image_rect.left() + image_rect.width() * self.swipe;
+
+                let mut clip = response.response.rect;
+                clip.min.x = clip.min.x.max(divider);
+                paint_overlay(ui, &response, second.id, second_rect(&response, &content.second.pixels), Color32::WHITE, Some(clip));
+
+                ui.painter_at(response.response.rect).vline(
+                    divider,
+                    image_rect.y_range(),
+                    Stroke::new(2.0, ui.visuals().selection.stroke.color),
+                );
+            }
+            ComparisonMode::Difference => {
+                let difference = content.difference.textures.texture(&content.difference.pixels);
+                self.viewer.ui(ui, difference, None, Orientation::NoTransforms);
+            }
+        }
+    }
+
+    fn summary_ui(ui: &mut Ui, summary: &DiffSummary) {
+        ui.horizontal(|ui| {
+            let percentage = match summary.total_pixels {
+                0 => 0.0,
+                total => summary.changed_pixels as f64 * 100.0 / total as f64,
+            };
+            ui.label(format!(
+                "{} {} / {} ({:.2}%)",
+                tr!("comparison-changed-pixels"),
+                summary.changed_pixels,
+                summary.total_pixels,
+                percentage
+            ));
+            ui.separator();
+
+            let psnr = match summary.psnr {
+                Some(psnr) => format!("{:.2} dB", psnr),
+                None => tr!("comparison-psnr-infinite"),
+            };
+            ui.label(format!("{} {}", tr!("comparison-psnr"), psnr));
+
+            if !summary.same_dimensions {
+                ui.separator();
+                ui.colored_label(ui.visuals().warn_fg_color, tr!("comparison-different-dimensions"));
+            }
+        });
+    }
+}
+
+/// the screen rectangle for the second image, which may be a different size to the first.
+fn second_rect(response: &ImageViewerResponse, pixels: &RgbaImage) -> Rect {
+    let size = vec2(pixels.width() as f32, pixels.height() as f32) * response.pixel_size();
+    Rect::from_min_size(response.image_rect.min, size)
+}
+
+fn paint_overlay(
+    ui: &Ui,
+    response: &ImageViewerResponse,
+    texture_id: egui::TextureId,
+    rect: Rect,
+    tint: Color32,
+    clip: Option<Rect>,
+) {
+    let clip = clip.unwrap_or(response.response.rect);
+    let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+    ui.painter_at(clip).image(texture_id, rect, uv, tint);
+}
+
+/// compares the images pixel-by-pixel, images of different sizes are aligned at the top-left and pixels outside
+/// the overlapping area count as changed, and as the maximum error for the PSNR.
+///
+/// returns a visualization of the differences, and the summary.
+pub fn compare(first: &RgbaImage, second: &RgbaImage) -> (RgbaImage, DiffSummary) {
+    let width = first.width().max(second.width());
+    let height = first.height().max(second.height());
+
+    let mut difference = RgbaImage::new(width, height);
+    let mut changed_pixels = 0_u64;
+    let mut squared_error = 0_f64;
+    let mut compared_samples = 0_u64;
+
+    for (x, y, target) in difference.enumerate_pixels_mut() {
+        let a = first.get_pixel_checked(x, y);
+        let b = second.get_pixel_checked(x, y);
+
+        *target = match (a, b) {
+            (Some(a), Some(b)) => {
+                let mut max_delta = 0_u8;
+                for (a, b) in a.0.iter().zip(b.0.iter()) {
+                    let delta = a.abs_diff(*b);
+                    max_delta = max_delta.max(delta);
+                    squared_error += (delta as f64).powi(2);
+                }
+                compared_samples += 4;
+
+                if max_delta == 0 {
+                    // unchanged pixels are shown dimmed, so the changes stand out.
+                    let [r, g, b, _a] = a.0;
+                    let grey = ((r as u16 + g as u16 + b as u16) / 3 / 4) as u8;
+                    Rgba([grey, grey, grey, 255])
+                } else {
+                    changed_pixels += 1;
+                    // brighter for larger differences, but always clearly visible
+                    let intensity = 128 + max_delta / 2;
+                    Rgba([intensity, 0, intensity / 2, 255])
+                }
+            }
+            // outside one of the images
+            _ => {
+                squared_error += 4.0 * 255.0_f64.powi(2);
+                compared_samples += 4;
+                changed_pixels += 1;
+                Rgba([255, 0, 128, 255])
+            }
+        };
+    }
+
+    let psnr = if squared_error == 0.0 {
+        None
+    } else {
+        let mse = squared_error / compared_samples as f64;
+        Some(10.0 * (255.0_f64.powi(2) / mse).log10())
+    };
+
+    let summary = DiffSummary {
+        changed_pixels,
+        total_pixels: width as u64 * height as u64,
+        psnr,
+        same_dimensions: first.dimensions() == second.dimensions(),
+    };
+
+    (difference, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_images() {
+        let image = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 255]));
+        let (_difference, summary) = compare(&image, &image);
+        assert_eq!(summary.changed_pixels, 0);
+        assert_eq!(summary.psnr, None);
+        assert!(summary.same_dimensions);
+    }
+
+    #[test]
+    fn different_dimensions_with_identical_overlap() {
+        let first = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 255]));
+        let second = RgbaImage::from_pixel(2, 1, Rgba([10, 20, 30, 255]));
+        let (_difference, summary) = compare(&first, &second);
+        assert_eq!(summary.changed_pixels, 2);
+        assert_eq!(summary.total_pixels, 4);
+        assert!(summary.psnr.is_some_and(f64::is_finite));
+        assert!(!summary.same_dimensions);
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use log::info;
+
+/// Files dropped onto the web build have no path on disk, only their bytes, which are kept here, see
+/// `add_memory_file`.  Reading a file checks here first, then falls back to the file system.
+static MEMORY_FILES: LazyLock<Mutex<HashMap<PathBuf, Arc<[u8]>>>> = LazyLock::new(Default::default);
+/// makes the paths of memory files unique, so dropping two files with the same name does not replace the first.
+static NEXT_MEMORY_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// the root of the paths of memory files, see `add_memory_file`.
+const MEMORY_FILES_ROOT: &str = "/memory";
+
+/// `true` for files that only exist in memory, they are not available after restarting.
+///
+/// this uses the path, not `MEMORY_FILES`, since tabs for memory files can be restored from a previous session, where
+/// the file is no-longer in memory.
+pub fn is_memory_file(path: &Path) -> bool {
+    path.starts_with(MEMORY_FILES_ROOT)
+}
+
+/// returns a new path for the file, e.g. `/memory/3/name.txt`.  It's absolute so it can be used to make a `file://`
+/// url, and it ends with the name, so it's used as the title of the tab.
+///
+/// each dropped file has its own path, so it's only open in one tab, which removes it when closed.
+pub fn add_memory_file(name: &str, bytes: Arc<[u8]>) -> PathBuf {
+    let id = NEXT_MEMORY_FILE_ID.fetch_add(1, Ordering::Relaxed);
+    let path = Path::new(MEMORY_FILES_ROOT).join(id.to_string()).join(name);
+
+    info!("Adding memory file. path: {:?}, length: {}", path, bytes.len());
+    MEMORY_FILES.lock().unwrap().insert(path.clone(), bytes);
+
+    path
+}
+
+pub fn remove_memory_file(path: &Path) {
+    if MEMORY_FILES.lock().unwrap().remove(path).is_some() {
+        info!("Removed memory file. path: {:?}", path);
+    }
+}
+
+fn memory_file(path: &Path) -> Option<Arc<[u8]>> {
+    MEMORY_FILES.lock().unwrap().get(path).cloned()
+}
+
+pub fn read(path: &Path) -> std::io::Result<Vec<u8>> {
+    match memory_file(path) {
+        Some(bytes) => Ok(bytes.to_vec()),
+        None => std::fs::read(path),
+    }
+}
+
+pub fn read_to_string(path: &Path) -> std::io::Result<String> {
+    match memory_file(path) {
+        Some(bytes) => String::from_utf8(bytes.to_vec())
+            .map_err(|cause| std::io::Error::new(std::io::ErrorKind::InvalidData, cause)),
+        None => std::fs::read_to_string(path),
+    }
+}
+
+/// the size of the file, in bytes.
+pub fn len(path: &Path) -> std::io::Result<u64> {
+    match memory_file(path) {
+        Some(bytes) => Ok(bytes.len() as u64),
+        None => std::fs::metadata(path).map(|metadata| metadata.len()),
+    }
+}
+
+/// for decoders that read the file incrementally, e.g. to read only the header.
+pub fn open(path: &Path) -> std::io::Result<FileReader> {
+    match memory_file(path) {
+        Some(bytes) => Ok(FileReader::Memory(Cursor::new(bytes))),
+        None => File::open(path).map(|file| FileReader::Disk(BufReader::new(file))),
+    }
+}
+
+pub enum FileReader {
+    Disk(BufReader<File>),
+    Memory(Cursor<Arc<[u8]>>),
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            FileReader::Disk(reader) => reader.read(buf),
+            FileReader::Memory(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for FileReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            FileReader::Disk(reader) => reader.fill_buf(),
+            FileReader::Memory(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match self {
+            FileReader::Disk(reader) => reader.consume(amount),
+            FileReader::Memory(reader) => reader.consume(amount),
+        }
+    }
+}
+
+impl Seek for FileReader {
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            FileReader::Disk(reader) => reader.seek(position),
+            FileReader::Memory(reader) => reader.seek(position),
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::sidebar::sidebar_ui;
+use crate::documents::{DocumentContext, DocumentKey};
+use egui::{Label, RichText, Sense, TextEdit, TextStyle, Ui};
+use egui_i18n::tr;
+use log::error;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+const BYTES_PER_ROW: usize = 16;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum SearchMode {
+    #[default]
+    Hex,
+    Text,
+}
+
+impl SearchMode {
+    /// `None` if the input is empty, or is not valid hex.
+    fn parse(&self, input: &str) -> Option<Vec<u8>> {
+        let pattern = match self {
+            SearchMode::Hex => {
+                let digits: String = input
+                    .trim()
+                    .trim_start_matches("0x")
+                    .chars()
+                    .filter(|char| !char.is_whitespace())
+                    .collect();
+                if digits.len() % 2 != 0 {
+                    return None;
+                }
+                // pairs of chars, not bytes, so non-ascii input is rejected instead of being split mid-char.
+                let digits = digits
+                    .chars()
+                    .map(|char| char.to_digit(16).map(|digit| digit as u8))
+                    .collect::<Option<Vec<u8>>>()?;
+                digits
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0] << 4) | pair[1])
+                    .collect::<Vec<u8>>()
+            }
+            SearchMode::Text => input.as_bytes().to_vec(),
+        };
+
+        (!pattern.is_empty()).then_some(pattern)
+    }
+}
+
+#[derive(Default)]
+struct Search {
+    mode: SearchMode,
+    input: String,
+    /// `Some(false)` when the last search did not find anything.
+    found: Option<bool>,
+}
+
+/// finds the next occurrence of the pattern after `from`, wrapping around to the start.
+fn find(bytes: &[u8], pattern: &[u8], from: usize) -> Option<usize> {
+    let position = |start: usize| {
+        bytes
+            .get(start..)?
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map(|position| position + start)
+    };
+
+    position(from).or_else(|| position(0))
+}
+
+/// accepts decimal, or hex with a `0x` prefix.
+fn parse_offset(input: &str) -> Option<usize> {
+    let input = input.trim();
+    match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+fn array<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+    bytes.get(..N)?.try_into().ok()
+}
+
+/// the bytes, starting at the first selected byte, interpreted as each of the numeric types.
+fn interpretations(bytes: &[u8], little_endian: bool) -> Vec<(&'static str, Option<String>)> {
+    macro_rules! interpret {
+        ($type:ty) => {
+            array(bytes).map(|array| {
+                match little_endian {
+                    true => <$type>::from_le_bytes(array),
+                    false => <$type>::from_be_bytes(array),
+                }
+                .to_string()
+            })
+        };
+    }
+
+    vec![
+        ("u8", interpret!(u8)),
+        ("i8", interpret!(i8)),
+        ("u16", interpret!(u16)),
+        ("i16", interpret!(i16)),
+        ("u32", interpret!(u32)),
+        ("i32", interpret!(i32)),
+        ("u64", interpret!(u64)),
+        ("i64", interpret!(i64)),
+        ("f32", interpret!(f32)),
+        ("f64", interpret!(f64)),
+    ]
+}
+
+enum HexLoaderError {
+    Io(std::io::Error),
+}
+
+/// Shows the bytes of any file, used for files that are not supported by any other document kind.
+pub struct HexDocument {
+    pub path: PathBuf,
+
+    loader: DocumentContent<Vec<u8>, HexLoaderError>,
+    /// the byte that was clicked first, and the byte that was shift-clicked, in either order.
+    selection: Option<(usize, usize)>,
+    little_endian: bool,
+    go_to_input: String,
+    go_to_error: bool,
+    search: Search,
+    /// the row to scroll to on the next frame.
+    scroll_to_row: Option<usize>,
+}
+
+impl HexDocument {
+    pub fn from_path(path: PathBuf, ctx: &egui::Context, document_key: DocumentKey, sender: AppMessageSender) -> Self {
+        let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let loader = DocumentContent::load(path.clone(), ctx, message, sender, |path, _ctx| {
+            files::read(&path).map_err(|cause| {
+                error!("Failed to load file. path: {:?}, cause: {}", path, cause);
+                HexLoaderError::Io(cause)
+            })
+        });
+
+        Self {
+            path,
+            loader,
+            selection: None,
+            little_endian: true,
+            go_to_input: String::new(),
+            go_to_error: false,
+            search: Search::default(),
+            scroll_to_row: None,
+        }
+    }
+
+    fn selected_range(&self) -> Option<RangeInclusive<usize>> {
+        self.selection.map(|(anchor, cursor)| anchor.min(cursor)..=anchor.max(cursor))
+    }
+
+    fn select(&mut self, range: RangeInclusive<usize>) {
+        self.selection = Some((*range.start(), *range.end()));
+        self.scroll_to_row = Some(range.start() / BYTES_PER_ROW);
+    }
+
+    pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
+        self.loader.update();
+
+        let unknown = || tr!("generic-unknown-value");
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (
+                tr!("hex-sidebar-size"),
+                self.loader
+                    .content()
+                    .map_or_else(unknown, |bytes| format!("{} B", bytes.len())),
+            ),
+            (
+                tr!("hex-sidebar-selection"),
+                self.selected_range().map_or_else(unknown, |range| {
+                    format!(
+                        "0x{:08X} - 0x{:08X} ({} B)",
+                        range.start(),
+                        range.end(),
+                        range.end() - range.start() + 1
+                    )
+                }),
+            ),
+        ];
+
+        sidebar_ui(ui, rows, |ui| {
+            if self.loader.content().is_some() {
+                self.go_to_ui(ui);
+                self.search_ui(ui);
+                self.interpretation_ui(ui);
+            }
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.content_ui(ui);
+        });
+    }
+
+    fn go_to_ui(&mut self, ui: &mut Ui) {
+        let Some(length) = self.loader.content().map(Vec::len) else {
+            return;
+        };
+
+        ui.heading(tr!("hex-go-to-heading"));
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut self.go_to_input)
+                    .hint_text("0x0000")
+                    .desired_width(100.0),
+            );
+            let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+
+            if ui.button(tr!("hex-go-to-button")).clicked() || submitted {
+                match parse_offset(&self.go_to_input).filter(|offset| *offset < length) {
+                    Some(offset) => {
+                        self.go_to_error = false;
+                        self.select(offset..=offset);
+                    }
+                    None => self.go_to_error = true,
+                }
+            }
+        });
+        if self.go_to_error {
+            ui.colored_label(ui.visuals().error_fg_color, tr!("hex-go-to-invalid"));
+        }
+    }
+
+    fn search_ui(&mut self, ui: &mut Ui) {
+        ui.heading(tr!("hex-search-heading"));
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.search.mode, SearchMode::Hex, tr!("hex-search-mode-hex"));
+            ui.selectable_value(&mut self.search.mode, SearchMode::Text, tr!("hex-search-mode-text"));
+        });
+
+        let pattern = self.search.mode.parse(&self.search.input);
+
+        ui.horizontal(|ui| {
+            let hint = match self.search.mode {
+                SearchMode::Hex => "DE AD BE EF",
+                SearchMode::Text => "",
+            };
+            let response = ui.add(
+                TextEdit::singleline(&mut self.search.input)
+                    .hint_text(hint)
+                    .desired_width(150.0),
+            );
+            if response.changed() {
+                self.search.found = None;
+            }
+            let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+
+            let find_button = ui.add_enabled(pattern.is_some(), egui::Button::new(tr!("hex-search-next")));
+            if let (Some(pattern), true) = (&pattern, find_button.clicked() || submitted) {
+                // searching starts after the start of the selection, so repeated searches find each occurrence in turn.
+                let from = self.selected_range().map_or(0, |range| range.start() + 1);
+                let found = self.loader.content().and_then(|bytes| find(bytes, pattern, from));
+
+                self.search.found = Some(found.is_some());
+                if let Some(offset) = found {
+                    self.select(offset..=offset + pattern.len() - 1);
+                }
+            }
+        });
+
+        if pattern.is_none() && !self.search.input.is_empty() {
+            ui.colored_label(ui.visuals().error_fg_color, tr!("hex-search-invalid"));
+        }
+        if self.search.found == Some(false) {
+            ui.label(tr!("hex-search-not-found"));
+        }
+    }
+
+    fn interpretation_ui(&mut self, ui: &mut Ui) {
+        ui.heading(tr!("hex-interpretation-heading"));
+
+        let (Some(bytes), Some(range)) = (self.loader.content(), self.selected_range()) else {
+            ui.label(tr!("hex-interpretation-no-selection"));
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.little_endian, true, tr!("hex-interpretation-little-endian"));
+            ui.selectable_value(&mut self.little_endian, false, tr!("hex-interpretation-big-endian"));
+        });
+
+        egui::Grid::new(ui.id().with("interpretation"))
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, value) in interpretations(bytes.get(*range.start()..).unwrap_or_default(), self.little_endian) {
+                    ui.monospace(name);
+                    match value {
+                        Some(value) => ui.monospace(value),
+                        None => ui.weak(tr!("generic-unknown-value")),
+                    };
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        if let Some(HexLoaderError::Io(cause)) = self.loader.error() {
+            ui.label(tr!("file-loading-error"));
+            ui.label(cause.to_string());
+            return;
+        }
+
+        let Some(bytes) = self.loader.content() else {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+            return;
+        };
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let row_count = bytes.len().div_ceil(BYTES_PER_ROW);
+        let selection = self.selected_range();
+        let selected_background = ui.visuals().selection.bg_fill;
+        let mut clicked = None;
+
+        let mut scroll_area = egui::ScrollArea::both().auto_shrink([false, false]);
+        if let Some(row) = self.scroll_to_row.take() {
+            // show the row in the middle of the view
+            let visible_rows = (ui.available_height() / (row_height + ui.spacing().item_spacing.y)) as usize;
+            let top_row = row.saturating_sub(visible_rows / 2);
+            scroll_area = scroll_area.vertical_scroll_offset(top_row as f32 * (row_height + ui.spacing().item_spacing.y));
+        }
+
+        scroll_area.show_rows(ui, row_height, row_count, |ui, rows| {
+            for row in rows {
+                let start = row * BYTES_PER_ROW;
+                let row_bytes = &bytes[start..(start + BYTES_PER_ROW).min(bytes.len())];
+
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+
+                    ui.label(RichText::new(format!("{:08X}  ", start)).monospace().weak());
+
+                    let byte_ui = |ui: &mut Ui, offset: usize, text: String| {
+                        let mut text = RichText::new(text).monospace();
+                        if selection.as_ref().is_some_and(|range| range.contains(&offset)) {
+                            text = text.background_color(selected_background);
+                        }
+                        ui.add(Label::new(text).sense(Sense::click())).clicked()
+                    };
+
+                    for column in 0..BYTES_PER_ROW {
+                        // an extra space between the two groups of 8 bytes
+                        if column == BYTES_PER_ROW / 2 {
+                            ui.monospace(" ");
+                        }
+                        match row_bytes.get(column) {
+                            Some(byte) => {
+                                if byte_ui(ui, start + column, format!("{:02X}", byte)) {
+                                    clicked = Some(start + column);
+                                }
+                                ui.monospace(" ");
+                            }
+                            None => {
+                                ui.monospace("   ");
+                            }
+                        }
+                    }
+
+                    ui.monospace(" ");
+
+                    for (column, byte) in row_bytes.iter().enumerate() {
+                        let char = match byte.is_ascii_graphic() || *byte == b' ' {
+                            true => *byte as char,
+                            false => '.',
+                        };
+                        if byte_ui(ui, start + column, char.to_string()) {
+                            clicked = Some(start + column);
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(offset) = clicked {
+            // shift-click extends the selection
+            let extend = ui.input(|input| input.modifiers.shift);
+            self.selection = match (self.selection, extend) {
+                (Some((anchor, _)), true) => Some((anchor, offset)),
+                _ => Some((offset, offset)),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!(SearchMode::Hex.parse("de ad BE EF"), Some(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(SearchMode::Hex.parse(" 0x0a0b "), Some(vec![0x0a, 0x0b]));
+        assert_eq!(SearchMode::Hex.parse("abc"), None);
+        assert_eq!(SearchMode::Hex.parse("zz"), None);
+        assert_eq!(SearchMode::Hex.parse(""), None);
+    }
+
+    #[test]
+    fn parse_hex_non_ascii() {
+        assert_eq!(SearchMode::Hex.parse("aéb"), None);
+        assert_eq!(SearchMode::Hex.parse("é"), None);
+    }
+
+    #[test]
+    fn parse_text() {
+        assert_eq!(SearchMode::Text.parse("aé"), Some("aé".as_bytes().to_vec()));
+        assert_eq!(SearchMode::Text.parse(""), None);
+    }
+
+    #[test]
+    fn find_wraps_around() {
+        let bytes = [1, 2, 3, 1, 2, 3];
+        assert_eq!(find(&bytes, &[1, 2], 0), Some(0));
+        assert_eq!(find(&bytes, &[1, 2], 1), Some(3));
+        assert_eq!(find(&bytes, &[1, 2], 4), Some(0));
+        assert_eq!(find(&bytes, &[4], 0), None);
+        assert_eq!(find(&bytes, &[1, 2], 100), Some(0));
+    }
+
+    #[test]
+    fn parse_offsets() {
+        assert_eq!(parse_offset("42"), Some(42));
+        assert_eq!(parse_offset(" 0x1F "), Some(0x1f));
+        assert_eq!(parse_offset("0X10"), Some(0x10));
+        assert_eq!(parse_offset("0x"), None);
+        assert_eq!(parse_offset("-1"), None);
+        assert_eq!(parse_offset("abc"), None);
+    }
+}
@@ -1,31 +1,120 @@
+use crate::documents::sidebar::sidebar_ui;
 use crate::documents::{DocumentContext, DocumentKey};
-use egui::{frame, Color32, ColorImage, Context, Image, ImageData, ImageSource, SizeHint, TextureHandle, TextureOptions, Ui};
+use egui::{Context, Ui};
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
-use eframe::epaint::Margin;
-use egui::load::{SizedTexture, TexturePoll};
 use egui_i18n::tr;
-use egui_taffy::taffy::prelude::{auto, fit_content, fr, length, percent};
-use egui_taffy::taffy::{AlignItems, Display, FlexDirection, Size, Style};
-use egui_taffy::{tui, TuiBuilderLogic};
-use image::ImageReader;
-use image::GenericImageView;
+use image::{Rgba, RgbaImage};
+use image::metadata::Orientation;
 use log::{debug, error, info};
 use url::Url;
 use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::image::metadata::{format_file_size, ImageMetadata};
+use crate::documents::image::decoder::{decode, DecodeError, DecoderSettings};
+use crate::documents::image::export::ImageExporter;
+use crate::documents::image::editing::{save_pixels, EditOperation, EditorAction, ImageEditor, ImageTool};
+use crate::documents::image::histogram::{histogram_ui, Channel, Histogram};
+use crate::documents::image::inspector::{PixelInspector, PixelSample};
+use crate::documents::image::painting::PaintToolbox;
+use crate::documents::image::tiles::{ImageTextures, PixelRegion};
+use crate::documents::image::viewer::ImageViewer;
 use crate::documents::loader::DocumentContent;
 
+pub mod decoder;
+mod editing;
+mod export;
+mod histogram;
+mod inspector;
+mod metadata;
+mod painting;
+#[cfg(feature = "formats-svg")]
+mod svg;
+pub mod tiles;
+pub mod viewer;
+
 pub struct ImageDocument {
     pub path: PathBuf,
 
-    loader: DocumentContent<(Url, TextureHandle), ImageLoaderError>,
+    loader: DocumentContent<ImageContent, ImageLoaderError>,
     context: Context,
+    viewer: ImageViewer,
+    inspector: PixelInspector,
+    editor: ImageEditor,
+    paint_toolbox: PaintToolbox,
+    tool: ImageTool,
+    exporter: ImageExporter,
+    /// the channel to display.
+    channel: Channel,
+    /// the viewer was hovered or focused on the last frame, the editor shortcuts only apply to this document then.
+    viewer_active: bool,
+}
+
+struct ImageContent {
+    url: Url,
+    textures: ImageTextures,
+
+    /// the decoded pixels, with the orientation already applied.
+    pixels: RgbaImage,
+
+    histogram: Histogram,
+
+    /// created on demand when a single channel is displayed.
+    channel_view: Option<ChannelView>,
+
+    /// `None` for new images that have not been saved yet, or if the metadata could not be read.
+    metadata: Option<ImageMetadata>,
+}
+
+impl ImageContent {
+    /// the textures and pixels to display for the channel.
+    fn display(&mut self, ctx: &Context, channel: Channel) -> (&mut ImageTextures, &RgbaImage) {
+        let is_current = self
+            .channel_view
+            .as_ref()
+            .is_some_and(|view| view.channel == channel);
+
+        if !is_current {
+            let Some(pixels) = channel.to_image(&self.pixels) else {
+                // all channels, i.e. the image itself
+                return (&mut self.textures, &self.pixels);
+            };
+
+            let name = format!("{}#{:?}", self.url, channel);
+            let textures = ImageTextures::new(ctx, name, &pixels, ImageTextures::max_texture_side(ctx));
+            self.channel_view = Some(ChannelView {
+                channel,
+                pixels,
+                textures,
+            });
+        }
+
+        let view = self.channel_view.as_mut().unwrap();
+        (&mut view.textures, &view.pixels)
+    }
+
+    /// call after the pixels have been changed, to update the textures and the histogram.
+    fn upload_pixels(&mut self) {
+        self.textures.update(&self.pixels);
+        self.histogram = Histogram::compute(&self.pixels);
+        // re-created on demand
+        self.channel_view = None;
+    }
+
+    /// call after some of the pixels have been changed, while painting, only the textures are updated.
+    ///
+    /// the histogram and channel view are updated by `upload_pixels` when the operation is completed.
+    fn upload_region(&mut self, ctx: &Context, region: PixelRegion) {
+        self.textures.update_region(ctx, &self.pixels, region);
+    }
+}
+
+struct ChannelView {
+    channel: Channel,
+    pixels: RgbaImage,
+    textures: ImageTextures,
 }
 
 enum ImageLoaderError {
-    Error
+    Decode(DecodeError),
 }
 
 impl Drop for ImageDocument {
@@ -35,93 +124,103 @@ impl Drop for ImageDocument {
 }
 
 impl ImageDocument {
-    pub fn create_new(path: PathBuf, ctx: &Context) -> Self {
+    /// `background` is unmultiplied sRGBA
+    pub fn create_new(path: PathBuf, ctx: &Context, width: u32, height: u32, background: [u8; 4]) -> Self {
 
         let url = Url::from_file_path(path.clone()).unwrap();
         info!("creating image. uri: {}", url);
 
-        let image_data: ImageData = ImageData::Color(Arc::new(ColorImage::filled([100, 100], Color32::RED)));
+        let pixels = RgbaImage::from_pixel(width, height, Rgba(background));
 
-        let texture_handle = ctx.load_texture(
-            url.as_str(),
-            image_data,
-            Default::default()
-        );
+        let textures = ImageTextures::new(ctx, url.to_string(), &pixels, ImageTextures::max_texture_side(ctx));
 
         Self {
             path,
             context: ctx.clone(),
-            loader: DocumentContent::new((url, texture_handle)),
+            loader: DocumentContent::new(ImageContent {
+                url,
+                textures,
+                histogram: Histogram::compute(&pixels),
+                pixels,
+                channel_view: None,
+                metadata: None,
+            }),
+            viewer: ImageViewer::default(),
+            inspector: PixelInspector::default(),
+            // the image only exists in memory until it is saved.
+            editor: ImageEditor::new(false),
+            paint_toolbox: PaintToolbox::default(),
+            tool: ImageTool::default(),
+            exporter: ImageExporter::default(),
+            channel: Channel::default(),
+            viewer_active: false,
         }
     }
 
-    pub fn from_path(path: PathBuf, ctx: &Context, document_key: DocumentKey, sender: AppMessageSender) -> Self {
+    pub fn from_path(
+        path: PathBuf,
+        ctx: &Context,
+        document_key: DocumentKey,
+        sender: AppMessageSender,
+        settings: DecoderSettings,
+    ) -> Self {
         let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let max_texture_side = ImageTextures::max_texture_side(ctx);
         let loader = DocumentContent::load(path.clone(), ctx, message, sender, move |path, ctx| {
-            fn load_image_from_file_using_image_crate(ctx: &Context, path: PathBuf, url: Url) -> Option<(Url, TextureHandle)> {
-                // Open and decode the image
-                let img = ImageReader::open(path).ok()?.decode().ok()?;
-                let size = img.dimensions();
-
-                // Convert image to RGBA8
-                let rgba = img.to_rgba8();
-                let pixels = rgba.as_flat_samples();
-
-                // Create an egui ColorImage
-                let color_image = egui::ColorImage::from_rgba_unmultiplied([size.0 as usize, size.1 as usize], pixels.as_slice());
-
-                // Load the texture into egui, we use the url, so we can forget it later, forgetting an image requires a url, not a path...
-                let texture_handle = ctx.load_texture(url.as_str(), color_image, TextureOptions::default());
-                    
-                Some((url, texture_handle))
-            }
-            fn load_image_from_file_using_egui_extras(ctx: &Context, _path: PathBuf, url: Url) -> Option<(Url, TextureHandle)> {
-                let texture = loop {
-                    let poll = ctx.try_load_texture(url.as_str(), TextureOptions::default(), SizeHint::default()).ok()?;
-                    match poll {
-                        TexturePoll::Pending { .. } => {
-                            debug!("texture poll pending");
-                            thread::sleep(Duration::from_millis(100));
-                        }
-                        TexturePoll::Ready { texture } => {
-                            debug!("texture poll ready");
-                            break texture;
-                        }
-                    }
-                };
-
-                // very important that the id is given to the texture manager to prevent double-free/use-after-free panics.
-                ctx.tex_manager().write().retain(texture.id);
-                let texture_handle = TextureHandle::new(ctx.tex_manager(), texture.id);
-                Some((url, texture_handle))
-            }
-
             let url = Url::from_file_path(path.clone()).unwrap();
             info!("uri: {}", url);
 
-            //let result = load_image_from_file_using_image_crate(ctx, path, url);
-            let result = load_image_from_file_using_egui_extras(ctx, path, url);
-            match result {
-                None => {
-                    error!("Failed to load image");
-                    Err(ImageLoaderError::Error)
-                }
-                Some(result) => {
-                    info!("Image loaded. texture_id: {:?}", result.1.id());
-                    Ok(result)
-                }
+            let metadata = ImageMetadata::read(&path);
+            debug!("metadata: {:?}", metadata);
+
+            debug!("decoding image. strategy: {:?}", settings.strategy);
+            let mut img = decode(&path, &settings)
+                .inspect_err(|cause| error!("Failed to load image. path: {:?}, cause: {}", path, cause))
+                .map_err(ImageLoaderError::Decode)?;
+
+            // apply the orientation to the pixels, so that pixel coordinates match what is displayed.
+            if let Some(metadata) = &metadata {
+                img.apply_orientation(metadata.orientation);
             }
+
+            // Convert image to RGBA8, keeping the pixels for later use
+            let pixels = img.to_rgba8();
+
+            // upload once, we use the url, so we can forget it later, forgetting an image requires a url, not a path...
+            // images larger than the maximum texture size are uploaded as a downscaled preview.
+            let textures = ImageTextures::new(ctx, url.to_string(), &pixels, max_texture_side);
+            info!("Image loaded. texture_id: {:?}", textures.id());
+
+            let histogram = Histogram::compute(&pixels);
+
+            Ok(ImageContent {
+                url,
+                textures,
+                pixels,
+                histogram,
+                channel_view: None,
+                metadata,
+            })
         });
 
         Self {
             path,
             loader,
             context: ctx.clone(),
+            viewer: ImageViewer::default(),
+            inspector: PixelInspector::default(),
+            editor: ImageEditor::new(true),
+            paint_toolbox: PaintToolbox::default(),
+            tool: ImageTool::default(),
+            exporter: ImageExporter::default(),
+            channel: Channel::default(),
+            viewer_active: false,
         }
     }
 
     fn forget_existing_image(&mut self) {
-        if let Some((uri, _existing_texture)) = self.loader.take() {
+        if let Some(content) = self.loader.take() {
+            let uri = content.url;
             debug!("forgetting existing image. uri: {}", uri);
             
             // forget the image so that the image is loaded from disk again.
@@ -129,95 +228,209 @@ impl ImageDocument {
         }
     }
 
+    fn perform_edit_action(&mut self, action: EditorAction) {
+        let Some(content) = self.loader.content_mut() else {
+            return;
+        };
+        let pixels = &mut content.pixels;
+
+        match action {
+            EditorAction::Save => {
+                info!("saving image. path: {:?}", self.path);
+                let result = save_pixels(pixels, &self.path);
+                if let Err(cause) = &result {
+                    error!("Failed to save image. path: {:?}, cause: {}", self.path, cause);
+                } else {
+                    content.metadata = ImageMetadata::read(&self.path);
+                }
+                self.editor.saved(result);
+            }
+            action => {
+                if self.editor.perform(action, pixels) {
+                    content.upload_pixels();
+                }
+            }
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.editor.is_modified()
+    }
+
     pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
         self.loader.update();
 
-        ui.ctx().style_mut(|style| {
-            // if this is not done, text in labels/checkboxes/etc wraps
-            style.wrap_mode = Some(egui::TextWrapMode::Extend);
-            style.spacing.window_margin = Margin::same(0);
-        });
+        // other documents, in other tabs, also have shortcuts, so they're only consumed by the active document.
+        if self.loader.content().is_some() && self.viewer_active {
+            if let Some(action) = self.editor.shortcuts(ui) {
+                self.perform_edit_action(action);
+            }
+        }
+        let mut edit_action = None;
 
-        let default_style = || Style {
-            padding: length(2.),
-            gap: length(2.),
-            ..Default::default()
-        };
+        let metadata = self
+            .loader
+            .content()
+            .and_then(|content| content.metadata.as_ref());
+        let mut rows = vec![(tr!("document-sidebar-file-path"), self.path.display().to_string())];
+        rows.extend(Self::metadata_rows(metadata));
 
-        let mut frame = frame::Frame::new();
-        frame.outer_margin = Margin::same(0);
-        frame.inner_margin = Margin::same(0);
-
-        egui::SidePanel::left(ui.id().with("sidebar"))
-            .resizable(true)
-            .frame(frame)
-            .show_inside(ui, |ui| {
-                egui::ScrollArea::both().show(ui, |ui| {
-                    tui(ui, ui.id().with("grid"))
-                        .reserve_available_width()
-                        .style(Style {
-                            align_items: Some(AlignItems::Stretch),
-                            flex_direction: FlexDirection::Column,
-                            size: Size {
-                                width: percent(1.),
-                                height: auto(),
-                            },
-                            padding: length(0.),
-                            gap: length(0.),
-                            ..default_style()
-                        })
-                        .show(|tui| {
-                            tui.style(Style {
-                                flex_grow: 1.0,
-                                display: Display::Grid,
-                                grid_template_columns: vec![fit_content(percent(1.)), fr(1.)],
-                                grid_template_rows: vec![fr(1.), fr(1.)],
-
-                                // ensure items are centered vertically on rows
-                                align_items: Some(AlignItems::Center),
-                                padding: length(0.),
-                                margin: length(0.),
-                                ..default_style()
-                            })
-                                .add(|tui| {
-                                    tui.style(Style { ..default_style() })
-                                        .add_with_border(|tui| {
-                                            tui.label(tr!("document-sidebar-file-path"));
-                                        });
-                                    tui.style(Style {
-                                        flex_grow: 1.0,
-                                        ..default_style()
-                                    })
-                                        .add_with_border(|tui| {
-                                            tui.ui_add(egui::Label::new(self.path.display().to_string()))
-                                        });
-                                    // end of grid content
-                                });
-                            // end of container content
-                        });
-                    // end of scroll content
-                });
-                // end of sidebar content
-            });
+        sidebar_ui(ui, rows, |ui| {
+            if let Some(content) = self.loader.content() {
+                self.paint_toolbox.ui(ui, &mut self.tool);
+                ui.separator();
+                self.inspector.ui(ui);
+                ui.separator();
+                histogram_ui(ui, &content.histogram, &mut self.channel);
+                ui.separator();
+                edit_action = self.editor.ui(ui, &content.pixels);
+                ui.separator();
+                self.exporter.ui(ui, &content.pixels, &self.path);
+            }
+        });
+
+        if let Some(action) = edit_action {
+            self.perform_edit_action(action);
+        }
 
         egui::CentralPanel::default().show_inside(ui, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                self.content_ui(ui);
-            });
+            // Note: no scroll area here, the viewer handles scrolling and panning itself.
+            self.content_ui(ui);
+        });
+    }
+
+    fn metadata_rows(metadata: Option<&ImageMetadata>) -> Vec<(String, String)> {
+        let unknown = || tr!("generic-unknown-value");
+
+        let exif = metadata.and_then(|metadata| metadata.exif.as_ref());
+
+        vec![
+            (
+                tr!("image-sidebar-dimensions"),
+                metadata.map_or_else(unknown, |metadata| {
+                    format!("{} x {}", metadata.dimensions.0, metadata.dimensions.1)
+                }),
+            ),
+            (
+                tr!("image-sidebar-color-type"),
+                metadata.map_or_else(unknown, |metadata| format!("{:?}", metadata.color_type)),
+            ),
+            (
+                tr!("image-sidebar-bit-depth"),
+                metadata.map_or_else(unknown, |metadata| metadata.bits_per_pixel().to_string()),
+            ),
+            (
+                tr!("image-sidebar-format"),
+                metadata
+                    .and_then(|metadata| metadata.format_name())
+                    .unwrap_or_else(unknown),
+            ),
+            (
+                tr!("image-sidebar-file-size"),
+                metadata.map_or_else(unknown, |metadata| format_file_size(metadata.file_size)),
+            ),
+            (
+                tr!("image-sidebar-dpi"),
+                metadata
+                    .and_then(|metadata| metadata.dpi)
+                    .map_or_else(unknown, |(x, y)| format!("{:.0} x {:.0}", x, y)),
+            ),
+            (
+                tr!("image-sidebar-orientation"),
+                metadata.map_or_else(unknown, |metadata| format!("{:?}", metadata.orientation)),
+            ),
+            (
+                tr!("image-sidebar-exif-camera"),
+                exif.and_then(|exif| exif.camera.clone())
+                    .unwrap_or_else(unknown),
+            ),
+            (
+                tr!("image-sidebar-exif-date"),
+                exif.and_then(|exif| exif.date_time.clone())
+                    .unwrap_or_else(unknown),
+            ),
+        ]
+    }
+
+    fn toolbar_ui(viewer: &mut ImageViewer, tool: &mut ImageTool, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(tool, ImageTool::Pan, tr!("image-toolbar-tool-pan"));
+            ui.selectable_value(tool, ImageTool::Select, tr!("image-toolbar-tool-select"));
+            ui.separator();
+
+            viewer.zoom_controls_ui(ui);
         });
     }
 
     fn content_ui(&mut self, ui: &mut Ui) {
-        if self.loader.is_error() {
+        if let Some(ImageLoaderError::Decode(cause)) = self.loader.error() {
             ui.label(tr!("file-loading-error"));
+            ui.label(cause.to_string());
         } else {
-            if let Some((_url, texture_handle)) = self.loader.content_mut() {
-                egui::Frame::new().show(ui, |ui| {
-                    let image_source = ImageSource::Texture(SizedTexture::from_handle(&texture_handle));
-                    let image = Image::new(image_source);
+            if let Some(content) = self.loader.content_mut() {
+                Self::toolbar_ui(&mut self.viewer, &mut self.tool, ui);
+                self.viewer.set_primary_drag_pans(self.tool == ImageTool::Pan);
+
+                // the displayed textures depend on the selected channel, the pixels are always the image itself.
+                let (textures, display_pixels) = content.display(ui.ctx(), self.channel);
+
+                let texture = textures.texture(display_pixels);
+                let nearest_texture = match self.viewer.wants_nearest_sampling() {
+                    true => textures.nearest_texture(ui.ctx(), display_pixels),
+                    false => None,
+                };
+
+                let viewer_response = egui::Frame::new()
+                    .show(ui, |ui| {
+                        // the orientation has already been applied to the pixels.
+                        self.viewer.ui(ui, texture, nearest_texture, Orientation::NoTransforms)
+                    })
+                    .inner;
+
+                let nearest = self.viewer.wants_nearest_sampling();
+                textures.paint_tiles(ui, &viewer_response, display_pixels, nearest);
+
+                // focused when clicked, so the shortcuts still apply when the pointer is moved to the sidebar.
+                let response = &viewer_response.response;
+                if response.clicked() || response.drag_started() {
+                    response.request_focus();
+                }
+                self.viewer_active = response.hovered() || response.has_focus();
+
+                if let ImageTool::Paint(paint_tool) = self.tool {
+                    let outcome = self.paint_toolbox.input(paint_tool, ui, &viewer_response, &mut content.pixels);
+                    let completed = outcome.completed.is_some();
+                    if let Some((operation, previous)) = outcome.completed {
+                        self.editor.record(EditOperation::Paint(operation), previous);
+                    }
+                    // while painting only the changed region is uploaded, everything is updated once completed.
+                    if completed {
+                        content.upload_pixels();
+                    } else if let Some(region) = outcome.changed_region {
+                        content.upload_region(ui.ctx(), region);
+                    }
+                }
 
-                    ui.add_sized(ui.available_size(), image);
-                });
+                let hovered_sample = viewer_response
+                    .hovered_pixel()
+                    .and_then(|position| PixelSample::from_pixels(&content.pixels, position));
+                self.inspector.set_hovered(hovered_sample);
+
+                match self.tool {
+                    ImageTool::Pan => {
+                        if viewer_response.response.clicked() {
+                            if let Some(sample) = hovered_sample {
+                                self.inspector.pin(sample);
+                            }
+                        }
+                    }
+                    ImageTool::Select => {
+                        self.editor.selection_input(ui, &viewer_response);
+                    }
+                    ImageTool::Paint(_) => {}
+                }
+                self.editor.paint_selection(ui, &viewer_response);
+                self.paint_toolbox.paint_preview(ui, &viewer_response);
             } else {
                 ui.spinner();
                 ui.label(tr!("file-loading"));
@@ -0,0 +1,147 @@
+use crate::documents::files::{self, FileReader};
+use image::{DynamicImage, ImageError, ImageFormat, ImageReader, Limits, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Which library decodes the image file, all strategies produce pixels that are uploaded to a texture once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum DecoderStrategy {
+    /// `image::ImageReader`, with the limits also applied while decoding.
+    #[default]
+    ImageCrate,
+    /// `egui_extras::image::load_image_bytes`, the same decoder the egui image loaders use.
+    ///
+    /// Note: egui stores premultiplied alpha, so partially transparent pixels may differ slightly.
+    EguiExtras,
+}
+
+impl DecoderStrategy {
+    pub const ALL: [DecoderStrategy; 2] = [DecoderStrategy::ImageCrate, DecoderStrategy::EguiExtras];
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct DecoderSettings {
+    pub strategy: DecoderStrategy,
+    /// the maximum width or height, in pixels.
+    pub max_dimension: u32,
+    /// the maximum size of the decoded pixels, in bytes.
+    pub max_memory: u64,
+}
+
+impl DecoderSettings {
+    /// applied while decoding, used by decoders that support limits.
+    pub fn limits(&self) -> Limits {
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(self.max_dimension);
+        limits.max_image_height = Some(self.max_dimension);
+        limits.max_alloc = Some(self.max_memory);
+        limits
+    }
+}
+
+impl Default for DecoderSettings {
+    fn default() -> Self {
+        Self {
+            strategy: DecoderStrategy::default(),
+            max_dimension: 32768,
+            max_memory: 2 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    TooLarge { width: u32, height: u32 },
+    /// the frames of an animation, together, use more than the maximum memory.
+    AnimationTooLarge { frames: usize },
+    Io(std::io::Error),
+    Image(ImageError),
+    Loader(String),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::TooLarge { width, height } => write!(f, "image too large, {} x {}", width, height),
+            DecodeError::AnimationTooLarge { frames } => write!(f, "animation too large, more than {} frames", frames),
+            DecodeError::Io(cause) => write!(f, "{}", cause),
+            DecodeError::Image(cause) => write!(f, "{}", cause),
+            DecodeError::Loader(cause) => write!(f, "{}", cause),
+        }
+    }
+}
+
+impl From<std::io::Error> for DecodeError {
+    fn from(cause: std::io::Error) -> Self {
+        DecodeError::Io(cause)
+    }
+}
+
+impl From<ImageError> for DecodeError {
+    fn from(cause: ImageError) -> Self {
+        DecodeError::Image(cause)
+    }
+}
+
+/// decodes the image file, call from a background thread.
+///
+/// the limits are checked using the image header, before any pixels are decoded.
+pub fn decode(path: &Path, settings: &DecoderSettings) -> Result<DynamicImage, DecodeError> {
+    #[cfg(feature = "formats-svg")]
+    if super::svg::is_svg(path) {
+        return super::svg::rasterize(path, settings).map(DynamicImage::ImageRgba8);
+    }
+
+    let (width, height) = image_reader(path)?
+        .with_guessed_format()?
+        .into_dimensions()?;
+    check_limits(width, height, settings)?;
+
+    match settings.strategy {
+        DecoderStrategy::ImageCrate => {
+            let mut reader = image_reader(path)?.with_guessed_format()?;
+            reader.limits(settings.limits());
+
+            Ok(reader.decode()?)
+        }
+        DecoderStrategy::EguiExtras => {
+            let bytes = files::read(path)?;
+            let color_image = egui_extras::image::load_image_bytes(&bytes)
+                .map_err(|cause| DecodeError::Loader(cause.to_string()))?;
+
+            let [width, height] = color_image.size;
+            let raw = color_image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_srgba_unmultiplied())
+                .collect();
+
+            RgbaImage::from_raw(width as u32, height as u32, raw)
+                .map(DynamicImage::ImageRgba8)
+                .ok_or_else(|| DecodeError::Loader("unexpected buffer size".to_string()))
+        }
+    }
+}
+
+/// like `ImageReader::open`, but the file may also be in memory, the format is taken from the extension.
+pub fn image_reader(path: &Path) -> std::io::Result<ImageReader<FileReader>> {
+    let mut reader = ImageReader::new(files::open(path)?);
+    if let Ok(format) = ImageFormat::from_path(path) {
+        reader.set_format(format);
+    }
+
+    Ok(reader)
+}
+
+pub fn check_limits(width: u32, height: u32, settings: &DecoderSettings) -> Result<(), DecodeError> {
+    // RGBA, 1 byte per channel, which is what is kept in memory and uploaded.
+    let memory = width as u64 * height as u64 * 4;
+
+    if width > settings.max_dimension || height > settings.max_dimension || memory > settings.max_memory {
+        return Err(DecodeError::TooLarge { width, height });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,528 @@
+use crate::documents::image::painting::{PaintOperation, PaintTool};
+use crate::documents::image::viewer::ImageViewerResponse;
+use egui::{Color32, ComboBox, DragValue, Key, KeyboardShortcut, Modifiers, PointerButton, Stroke, StrokeKind, Ui};
+use egui_i18n::tr;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageError, ImageFormat, Rgba, RgbaImage};
+use std::path::Path;
+
+/// older steps are discarded, each step keeps a copy of the pixels.
+const MAX_UNDO_STEPS: usize = 32;
+/// older steps are also discarded when the copies of the pixels use more memory than this, e.g. for large images.
+const MAX_UNDO_BYTES: usize = 512 * 1024 * 1024;
+
+const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+const SAVE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::S);
+
+/// What the primary mouse button does in the image viewer.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImageTool {
+    #[default]
+    Pan,
+    Select,
+    Paint(PaintTool),
+}
+
+/// A rectangle of pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    /// a rectangle that includes both corner pixels.
+    pub fn from_corners(a: [u32; 2], b: [u32; 2]) -> Self {
+        let (x, y) = (a[0].min(b[0]), a[1].min(b[1]));
+
+        Self {
+            x,
+            y,
+            width: a[0].max(b[0]) - x + 1,
+            height: a[1].max(b[1]) - y + 1,
+        }
+    }
+
+    pub fn min(&self) -> [u32; 2] {
+        [self.x, self.y]
+    }
+
+    /// exclusive
+    pub fn max(&self) -> [u32; 2] {
+        [self.x + self.width, self.y + self.height]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CanvasAnchor {
+    TopLeft,
+    Center,
+}
+
+#[derive(Clone, Debug)]
+pub enum EditOperation {
+    RotateClockwise,
+    RotateCounterClockwise,
+    FlipHorizontal,
+    FlipVertical,
+    Crop(PixelRect),
+    Resize {
+        width: u32,
+        height: u32,
+        filter: FilterType,
+    },
+    /// changes the size of the image without scaling the content, new areas are filled with `fill`.
+    ResizeCanvas {
+        width: u32,
+        height: u32,
+        anchor: CanvasAnchor,
+        fill: Rgba<u8>,
+    },
+    Paint(PaintOperation),
+}
+
+impl EditOperation {
+    pub fn apply(&self, pixels: &RgbaImage) -> RgbaImage {
+        match self {
+            EditOperation::RotateClockwise => imageops::rotate90(pixels),
+            EditOperation::RotateCounterClockwise => imageops::rotate270(pixels),
+            EditOperation::FlipHorizontal => imageops::flip_horizontal(pixels),
+            EditOperation::FlipVertical => imageops::flip_vertical(pixels),
+            EditOperation::Crop(rect) => imageops::crop_imm(pixels, rect.x, rect.y, rect.width, rect.height).to_image(),
+            EditOperation::Resize { width, height, filter } => imageops::resize(pixels, *width, *height, *filter),
+            EditOperation::ResizeCanvas {
+                width,
+                height,
+                anchor,
+                fill,
+            } => {
+                let mut canvas = RgbaImage::from_pixel(*width, *height, *fill);
+                let (x, y) = match anchor {
+                    CanvasAnchor::TopLeft => (0, 0),
+                    CanvasAnchor::Center => (
+                        (*width as i64 - pixels.width() as i64) / 2,
+                        (*height as i64 - pixels.height() as i64) / 2,
+                    ),
+                };
+                imageops::replace(&mut canvas, pixels, x, y);
+                canvas
+            }
+            EditOperation::Paint(operation) => {
+                let mut painted = pixels.clone();
+                operation.paint(&mut painted);
+                painted
+            }
+        }
+    }
+}
+
+struct EditStep {
+    operation: EditOperation,
+    /// the pixels before the operation was applied
+    previous: RgbaImage,
+}
+
+/// Undo/redo history, undo restores a copy of the pixels, redo applies the operation again.
+struct EditHistory {
+    undo_stack: Vec<EditStep>,
+    redo_stack: Vec<EditOperation>,
+
+    /// the length of the undo stack when the pixels were last saved, `None` if that state is no-longer reachable.
+    saved_depth: Option<usize>,
+}
+
+impl EditHistory {
+    fn new(saved: bool) -> Self {
+        Self {
+            undo_stack: vec![],
+            redo_stack: vec![],
+            saved_depth: saved.then_some(0),
+        }
+    }
+
+    fn apply(&mut self, operation: EditOperation, pixels: &mut RgbaImage) {
+        let edited = operation.apply(pixels);
+        let previous = std::mem::replace(pixels, edited);
+        self.push(operation, previous);
+    }
+
+    /// records an operation that has already been applied to the pixels.
+    fn push(&mut self, operation: EditOperation, previous: RgbaImage) {
+        self.undo_stack.push(EditStep { operation, previous });
+        self.redo_stack.clear();
+
+        if self.saved_depth.is_some_and(|depth| depth >= self.undo_stack.len()) {
+            self.saved_depth = None;
+        }
+
+        // the latest step is always kept, so the last edit can be undone.
+        while self.undo_stack.len() > 1
+            && (self.undo_stack.len() > MAX_UNDO_STEPS || self.undo_bytes() > MAX_UNDO_BYTES)
+        {
+            self.undo_stack.remove(0);
+            self.saved_depth = self.saved_depth.and_then(|depth| depth.checked_sub(1));
+        }
+    }
+
+    fn undo_bytes(&self) -> usize {
+        self.undo_stack
+            .iter()
+            .map(|step| step.previous.as_raw().len())
+            .sum()
+    }
+
+    fn undo(&mut self, pixels: &mut RgbaImage) -> bool {
+        match self.undo_stack.pop() {
+            Some(EditStep { operation, previous }) => {
+                *pixels = previous;
+                self.redo_stack.push(operation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn redo(&mut self, pixels: &mut RgbaImage) -> bool {
+        match self.redo_stack.pop() {
+            Some(operation) => {
+                let edited = operation.apply(pixels);
+                let previous = std::mem::replace(pixels, edited);
+                self.undo_stack.push(EditStep { operation, previous });
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn mark_saved(&mut self) {
+        self.saved_depth = Some(self.undo_stack.len());
+    }
+
+    fn is_modified(&self) -> bool {
+        self.saved_depth != Some(self.undo_stack.len())
+    }
+}
+
+pub enum EditorAction {
+    Apply(EditOperation),
+    Undo,
+    Redo,
+    Save,
+}
+
+struct ResizeForm {
+    width: u32,
+    height: u32,
+    keep_aspect_ratio: bool,
+    filter: FilterType,
+}
+
+struct CanvasForm {
+    width: u32,
+    height: u32,
+    anchor: CanvasAnchor,
+}
+
+/// Editing state for an image document, the pixels themselves are owned by the document.
+pub struct ImageEditor {
+    history: EditHistory,
+    selection: Option<PixelRect>,
+    selection_start: Option<[u32; 2]>,
+
+    /// the dimensions the forms were last initialized from.
+    form_dimensions: Option<(u32, u32)>,
+    resize: ResizeForm,
+    canvas: CanvasForm,
+
+    save_error: Option<String>,
+}
+
+impl ImageEditor {
+    /// `saved` should be `false` for new images that do not exist on disk yet.
+    pub fn new(saved: bool) -> Self {
+        Self {
+            history: EditHistory::new(saved),
+            selection: None,
+            selection_start: None,
+            form_dimensions: None,
+            resize: ResizeForm {
+                width: 0,
+                height: 0,
+                keep_aspect_ratio: true,
+                filter: FilterType::Lanczos3,
+            },
+            canvas: CanvasForm {
+                width: 0,
+                height: 0,
+                anchor: CanvasAnchor::Center,
+            },
+            save_error: None,
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.history.is_modified()
+    }
+
+    /// Applies the action to the pixels, returns `true` if the pixels were changed.
+    ///
+    /// `EditorAction::Save` is not handled here, as saving is done by the document, see `saved`.
+    pub fn perform(&mut self, action: EditorAction, pixels: &mut RgbaImage) -> bool {
+        let changed = match action {
+            EditorAction::Apply(operation) => {
+                self.history.apply(operation, pixels);
+                true
+            }
+            EditorAction::Undo => self.history.undo(pixels),
+            EditorAction::Redo => self.history.redo(pixels),
+            EditorAction::Save => false,
+        };
+
+        if changed {
+            // the selection may no-longer be within the image
+            self.selection = None;
+        }
+
+        changed
+    }
+
+    /// Records an operation that has already been applied to the pixels, e.g. painting, so that it can be undone.
+    pub fn record(&mut self, operation: EditOperation, previous: RgbaImage) {
+        self.history.push(operation, previous);
+    }
+
+    /// Call after the document has attempted to save the pixels.
+    pub fn saved(&mut self, result: Result<(), ImageError>) {
+        match result {
+            Ok(()) => {
+                self.history.mark_saved();
+                self.save_error = None;
+            }
+            Err(cause) => {
+                self.save_error = Some(cause.to_string());
+            }
+        }
+    }
+
+    pub fn shortcuts(&self, ui: &Ui) -> Option<EditorAction> {
+        // Note: redo must be checked first, as the undo shortcut also matches when shift is held.
+        ui.ctx().input_mut(|i| {
+            if i.consume_shortcut(&REDO_SHORTCUT) {
+                Some(EditorAction::Redo)
+            } else if i.consume_shortcut(&UNDO_SHORTCUT) {
+                Some(EditorAction::Undo)
+            } else if i.consume_shortcut(&SAVE_SHORTCUT) {
+                Some(EditorAction::Save)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Handles the select tool, call with the response from the viewer.
+    pub fn selection_input(&mut self, ui: &Ui, viewer_response: &ImageViewerResponse) {
+        let response = &viewer_response.response;
+
+        if response.drag_started_by(PointerButton::Primary) {
+            self.selection_start = ui
+                .input(|i| i.pointer.press_origin())
+                .and_then(|pos| viewer_response.pixel_at_clamped(pos));
+        }
+
+        if response.dragged_by(PointerButton::Primary) {
+            let current = response
+                .interact_pointer_pos()
+                .and_then(|pos| viewer_response.pixel_at_clamped(pos));
+
+            if let (Some(start), Some(current)) = (self.selection_start, current) {
+                self.selection = Some(PixelRect::from_corners(start, current));
+            }
+        }
+
+        if response.clicked_by(PointerButton::Primary) {
+            self.selection = None;
+        }
+    }
+
+    pub fn paint_selection(&self, ui: &Ui, viewer_response: &ImageViewerResponse) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+
+        let rect = viewer_response.screen_rect_of_pixels(selection.min(), selection.max());
+        let painter = ui.painter_at(viewer_response.response.rect);
+
+        // two strokes, so the selection is visible on both light and dark images.
+        painter.rect_stroke(rect, 0.0, Stroke::new(3.0, Color32::BLACK), StrokeKind::Outside);
+        painter.rect_stroke(rect, 0.0, Stroke::new(1.0, Color32::WHITE), StrokeKind::Outside);
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, pixels: &RgbaImage) -> Option<EditorAction> {
+        let dimensions = pixels.dimensions();
+        if self.form_dimensions != Some(dimensions) {
+            self.form_dimensions = Some(dimensions);
+            (self.resize.width, self.resize.height) = dimensions;
+            (self.canvas.width, self.canvas.height) = dimensions;
+        }
+
+        let mut action = None;
+
+        ui.heading(tr!("image-editor-heading"));
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.history.undo_stack.is_empty(), egui::Button::new(tr!("image-editor-undo")))
+                .clicked()
+            {
+                action = Some(EditorAction::Undo);
+            }
+            if ui
+                .add_enabled(!self.history.redo_stack.is_empty(), egui::Button::new(tr!("image-editor-redo")))
+                .clicked()
+            {
+                action = Some(EditorAction::Redo);
+            }
+            if ui.button(tr!("image-editor-save")).clicked() {
+                action = Some(EditorAction::Save);
+            }
+        });
+
+        if self.is_modified() {
+            ui.label(tr!("image-editor-modified"));
+        }
+        if let Some(save_error) = &self.save_error {
+            ui.colored_label(ui.visuals().error_fg_color, save_error);
+        }
+
+        ui.separator();
+
+        ui.horizontal_wrapped(|ui| {
+            let operations = [
+                (tr!("image-editor-rotate-clockwise"), EditOperation::RotateClockwise),
+                (tr!("image-editor-rotate-counter-clockwise"), EditOperation::RotateCounterClockwise),
+                (tr!("image-editor-flip-horizontal"), EditOperation::FlipHorizontal),
+                (tr!("image-editor-flip-vertical"), EditOperation::FlipVertical),
+            ];
+            for (label, operation) in operations {
+                if ui.button(label).clicked() {
+                    action = Some(EditorAction::Apply(operation));
+                }
+            }
+        });
+
+        if ui
+            .add_enabled(self.selection.is_some(), egui::Button::new(tr!("image-editor-crop")))
+            .on_disabled_hover_text(tr!("image-editor-crop-no-selection"))
+            .clicked()
+        {
+            if let Some(selection) = self.selection {
+                action = Some(EditorAction::Apply(EditOperation::Crop(selection)));
+            }
+        }
+
+        ui.separator();
+
+        //
+        // resize
+        //
+
+        ui.label(tr!("image-editor-resize"));
+        ui.horizontal(|ui| {
+            let (original_width, original_height) = dimensions;
+            let aspect_ratio = original_width as f32 / original_height as f32;
+
+            let width_changed = ui
+                .add(DragValue::new(&mut self.resize.width).range(1..=u16::MAX as u32))
+                .changed();
+            ui.label("x");
+            let height_changed = ui
+                .add(DragValue::new(&mut self.resize.height).range(1..=u16::MAX as u32))
+                .changed();
+
+            if self.resize.keep_aspect_ratio {
+                if width_changed {
+                    self.resize.height = ((self.resize.width as f32 / aspect_ratio).round() as u32).max(1);
+                } else if height_changed {
+                    self.resize.width = ((self.resize.height as f32 * aspect_ratio).round() as u32).max(1);
+                }
+            }
+        });
+        ui.checkbox(&mut self.resize.keep_aspect_ratio, tr!("image-editor-keep-aspect-ratio"));
+
+        ComboBox::from_id_salt(ui.id().with("resize_filter"))
+            .selected_text(filter_name(self.resize.filter))
+            .show_ui(ui, |ui| {
+                for filter in [
+                    FilterType::Nearest,
+                    FilterType::Triangle,
+                    FilterType::CatmullRom,
+                    FilterType::Gaussian,
+                    FilterType::Lanczos3,
+                ] {
+                    ui.selectable_value(&mut self.resize.filter, filter, filter_name(filter));
+                }
+            });
+
+        if ui.button(tr!("image-editor-apply-resize")).clicked() {
+            action = Some(EditorAction::Apply(EditOperation::Resize {
+                width: self.resize.width,
+                height: self.resize.height,
+                filter: self.resize.filter,
+            }));
+        }
+
+        ui.separator();
+
+        //
+        // canvas size
+        //
+
+        ui.label(tr!("image-editor-canvas-size"));
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut self.canvas.width).range(1..=u16::MAX as u32));
+            ui.label("x");
+            ui.add(DragValue::new(&mut self.canvas.height).range(1..=u16::MAX as u32));
+        });
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.canvas.anchor, CanvasAnchor::Center, tr!("image-editor-anchor-center"));
+            ui.selectable_value(&mut self.canvas.anchor, CanvasAnchor::TopLeft, tr!("image-editor-anchor-top-left"));
+        });
+
+        if ui.button(tr!("image-editor-apply-canvas-size")).clicked() {
+            action = Some(EditorAction::Apply(EditOperation::ResizeCanvas {
+                width: self.canvas.width,
+                height: self.canvas.height,
+                anchor: self.canvas.anchor,
+                fill: Rgba([0, 0, 0, 0]),
+            }));
+        }
+
+        action
+    }
+}
+
+fn filter_name(filter: FilterType) -> String {
+    match filter {
+        FilterType::Nearest => tr!("image-editor-filter-nearest"),
+        FilterType::Triangle => tr!("image-editor-filter-triangle"),
+        FilterType::CatmullRom => tr!("image-editor-filter-catmull-rom"),
+        FilterType::Gaussian => tr!("image-editor-filter-gaussian"),
+        FilterType::Lanczos3 => tr!("image-editor-filter-lanczos3"),
+    }
+}
+
+/// Writes the pixels to `path`, the format is determined by the extension.
+///
+/// Formats that do not support an alpha channel, e.g. JPEG, have it removed.
+pub fn save_pixels(pixels: &RgbaImage, path: &Path) -> Result<(), ImageError> {
+    let format = ImageFormat::from_path(path)?;
+    let image = DynamicImage::ImageRgba8(pixels.clone());
+
+    match format {
+        ImageFormat::Jpeg => image.into_rgb8().save_with_format(path, format),
+        _ => image.save_with_format(path, format),
+    }
+}
@@ -0,0 +1,278 @@
+use crate::file_picker::Picker;
+use egui::{Context, ProgressBar, Ui};
+use egui_i18n::tr;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageError, ImageFormat, RgbaImage};
+use log::{error, info};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/// the encoded image is written in chunks of this size, so that progress can be reported.
+const WRITE_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Png,
+    Jpeg,
+    Bmp,
+    WebP,
+    Tiff,
+    Gif,
+}
+
+impl ExportFormat {
+    const ALL: [ExportFormat; 6] = [
+        ExportFormat::Png,
+        ExportFormat::Jpeg,
+        ExportFormat::Bmp,
+        ExportFormat::WebP,
+        ExportFormat::Tiff,
+        ExportFormat::Gif,
+    ];
+
+    /// the formats that the `image` crate has been compiled with support for writing.
+    pub fn available() -> impl Iterator<Item = ExportFormat> {
+        Self::ALL
+            .into_iter()
+            .filter(|format| format.image_format().writing_enabled())
+    }
+
+    pub fn image_format(&self) -> ImageFormat {
+        match self {
+            ExportFormat::Png => ImageFormat::Png,
+            ExportFormat::Jpeg => ImageFormat::Jpeg,
+            ExportFormat::Bmp => ImageFormat::Bmp,
+            ExportFormat::WebP => ImageFormat::WebP,
+            ExportFormat::Tiff => ImageFormat::Tiff,
+            ExportFormat::Gif => ImageFormat::Gif,
+        }
+    }
+
+    /// e.g. `PNG`
+    pub fn name(&self) -> String {
+        self.image_format()
+            .extensions_str()
+            .first()
+            .map(|extension| extension.to_uppercase())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Encode(ImageError),
+    Io(std::io::Error),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Encode(cause) => write!(f, "{}", cause),
+            ExportError::Io(cause) => write!(f, "{}", cause),
+        }
+    }
+}
+
+impl From<ImageError> for ExportError {
+    fn from(cause: ImageError) -> Self {
+        ExportError::Encode(cause)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(cause: std::io::Error) -> Self {
+        ExportError::Io(cause)
+    }
+}
+
+/// Updated by the export thread, read by the UI.
+enum ExportProgress {
+    Encoding,
+    /// 0.0 to 1.0
+    Writing(f32),
+    Finished(Result<PathBuf, ExportError>),
+}
+
+/// Writes a copy of the image to another file, possibly in another format.
+///
+/// The document itself is unchanged, i.e. it continues to refer to the original file.
+pub struct ImageExporter {
+    format: ExportFormat,
+    jpeg_quality: u8,
+    picker: Picker,
+    job: Option<Arc<Mutex<ExportProgress>>>,
+    /// the outcome of the last export, a path or an error message.
+    outcome: Option<Result<PathBuf, String>>,
+}
+
+impl Default for ImageExporter {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Png,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+            picker: Picker::default(),
+            job: None,
+            outcome: None,
+        }
+    }
+}
+
+impl ImageExporter {
+    pub fn ui(&mut self, ui: &mut Ui, pixels: &RgbaImage, document_path: &Path) {
+        ui.heading(tr!("image-export-heading"));
+
+        let busy = self.picker.is_picking() || self.job.is_some();
+
+        ui.add_enabled_ui(!busy, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("image-export-format"));
+                egui::ComboBox::from_id_salt(ui.id().with("export_format"))
+                    .selected_text(self.format.name())
+                    .show_ui(ui, |ui| {
+                        for format in ExportFormat::available() {
+                            ui.selectable_value(&mut self.format, format, format.name());
+                        }
+                    });
+            });
+
+            if self.format == ExportFormat::Jpeg {
+                ui.add(egui::Slider::new(&mut self.jpeg_quality, 1..=100).text(tr!("image-export-jpeg-quality")));
+            }
+
+            if ui.button(tr!("image-export-button")).clicked() {
+                self.pick_path(document_path);
+            }
+        });
+
+        if let Ok(path) = self.picker.picked() {
+            self.start(ui.ctx(), pixels.clone(), path);
+        }
+
+        self.job_ui(ui);
+
+        match &self.outcome {
+            Some(Ok(path)) => {
+                ui.label(format!("{} {}", tr!("image-export-finished"), path.display()));
+            }
+            Some(Err(message)) => {
+                ui.colored_label(ui.visuals().error_fg_color, message);
+            }
+            None => {}
+        }
+    }
+
+    fn pick_path(&mut self, document_path: &Path) {
+        let extensions: Vec<String> = self
+            .format
+            .image_format()
+            .extensions_str()
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect();
+
+        let stem = document_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let file_name = format!("{}.{}", stem, extensions[0]);
+
+        let directory = document_path.parent().map(Path::to_path_buf);
+
+        self.picker
+            .save_file(directory, file_name, self.format.name(), extensions);
+    }
+
+    fn start(&mut self, ctx: &Context, pixels: RgbaImage, path: PathBuf) {
+        info!("exporting image. path: {:?}, format: {:?}", path, self.format);
+
+        let progress = Arc::new(Mutex::new(ExportProgress::Encoding));
+        self.job = Some(progress.clone());
+        self.outcome = None;
+
+        let format = self.format;
+        let jpeg_quality = self.jpeg_quality;
+        let ctx = ctx.clone();
+
+        std::thread::Builder::new()
+            .name("export".to_owned())
+            .spawn(move || {
+                let result = export(&pixels, &path, format, jpeg_quality, &progress, &ctx).map(|_| path);
+                *progress.lock().unwrap() = ExportProgress::Finished(result);
+                ctx.request_repaint();
+            })
+            .unwrap();
+    }
+
+    fn job_ui(&mut self, ui: &mut Ui) {
+        let Some(job) = &self.job else {
+            return;
+        };
+
+        let mut guard = job.lock().unwrap();
+        match &*guard {
+            ExportProgress::Encoding => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("image-export-encoding"));
+                });
+            }
+            ExportProgress::Writing(fraction) => {
+                ui.add(ProgressBar::new(*fraction).show_percentage());
+            }
+            ExportProgress::Finished(_) => {
+                // the thread has finished, so nothing else refers to the progress now.
+                let ExportProgress::Finished(result) = std::mem::replace(&mut *guard, ExportProgress::Encoding) else {
+                    unreachable!()
+                };
+                drop(guard);
+
+                self.outcome = Some(result.map_err(|cause| {
+                    error!("Failed to export image. cause: {}", cause);
+                    format!("{} {}", tr!("image-export-failed"), cause)
+                }));
+                self.job = None;
+            }
+        }
+    }
+}
+
+fn export(
+    pixels: &RgbaImage,
+    path: &Path,
+    format: ExportFormat,
+    jpeg_quality: u8,
+    progress: &Mutex<ExportProgress>,
+    ctx: &Context,
+) -> Result<(), ExportError> {
+    // encode in memory first, the encoders do not report progress, but writing the file can.
+    let mut encoded = Vec::new();
+    let image = DynamicImage::ImageRgba8(pixels.clone());
+    match format {
+        // JPEG does not support transparency, and allows the quality to be specified
+        ExportFormat::Jpeg => image
+            .into_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, jpeg_quality))?,
+        _ => image.write_to(&mut Cursor::new(&mut encoded), format.image_format())?,
+    }
+
+    *progress.lock().unwrap() = ExportProgress::Writing(0.0);
+    ctx.request_repaint();
+
+    let mut file = File::create(path)?;
+    let mut written = 0;
+    for chunk in encoded.chunks(WRITE_CHUNK_SIZE) {
+        file.write_all(chunk)?;
+        written += chunk.len();
+
+        *progress.lock().unwrap() = ExportProgress::Writing(written as f32 / encoded.len() as f32);
+        ctx.request_repaint();
+    }
+    file.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use egui::{pos2, vec2, Color32, Sense, Shape, Stroke, Ui};
+use egui_i18n::tr;
+use image::{Rgba, RgbaImage};
+
+const BINS: usize = 256;
+const PLOT_HEIGHT: f32 = 64.0;
+
+/// The channel of the image that is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Channel {
+    /// all channels, i.e. the image itself.
+    #[default]
+    Rgba,
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    Luminance,
+}
+
+impl Channel {
+    const ALL: [Channel; 6] = [
+        Channel::Rgba,
+        Channel::Red,
+        Channel::Green,
+        Channel::Blue,
+        Channel::Alpha,
+        Channel::Luminance,
+    ];
+
+    fn label(&self) -> String {
+        match self {
+            Channel::Rgba => tr!("image-histogram-channel-rgba"),
+            Channel::Red => tr!("image-histogram-channel-red"),
+            Channel::Green => tr!("image-histogram-channel-green"),
+            Channel::Blue => tr!("image-histogram-channel-blue"),
+            Channel::Alpha => tr!("image-histogram-channel-alpha"),
+            Channel::Luminance => tr!("image-histogram-channel-luminance"),
+        }
+    }
+
+    /// the value of the channel, `None` for `Rgba`.
+    fn value(&self, rgba: [u8; 4]) -> Option<u8> {
+        let [r, g, b, a] = rgba;
+        match self {
+            Channel::Rgba => None,
+            Channel::Red => Some(r),
+            Channel::Green => Some(g),
+            Channel::Blue => Some(b),
+            Channel::Alpha => Some(a),
+            Channel::Luminance => Some(luminance(rgba)),
+        }
+    }
+
+    /// an opaque greyscale image of the channel, `None` for `Rgba`.
+    pub fn to_image(&self, pixels: &RgbaImage) -> Option<RgbaImage> {
+        if *self == Channel::Rgba {
+            return None;
+        }
+
+        let mut image = RgbaImage::new(pixels.width(), pixels.height());
+        for (target, source) in image.pixels_mut().zip(pixels.pixels()) {
+            let value = self.value(source.0).unwrap();
+            *target = Rgba([value, value, value, 255]);
+        }
+
+        Some(image)
+    }
+}
+
+/// Rec. 709 luma, computed from the sRGB values.
+fn luminance(rgba: [u8; 4]) -> u8 {
+    let [r, g, b, _a] = rgba;
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as u8
+}
+
+/// The number of pixels for each value of each channel.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    red: [u32; BINS],
+    green: [u32; BINS],
+    blue: [u32; BINS],
+    alpha: [u32; BINS],
+    luminance: [u32; BINS],
+}
+
+impl Histogram {
+    pub fn compute(pixels: &RgbaImage) -> Self {
+        let mut histogram = Self {
+            red: [0; BINS],
+            green: [0; BINS],
+            blue: [0; BINS],
+            alpha: [0; BINS],
+            luminance: [0; BINS],
+        };
+
+        for pixel in pixels.pixels() {
+            let [r, g, b, a] = pixel.0;
+            histogram.red[r as usize] += 1;
+            histogram.green[g as usize] += 1;
+            histogram.blue[b as usize] += 1;
+            histogram.alpha[a as usize] += 1;
+            histogram.luminance[luminance(pixel.0) as usize] += 1;
+        }
+
+        histogram
+    }
+}
+
+/// Shows the histograms and the channel selector.
+pub fn histogram_ui(ui: &mut Ui, histogram: &Histogram, channel: &mut Channel) {
+    ui.heading(tr!("image-histogram-heading"));
+
+    ui.horizontal_wrapped(|ui| {
+        for candidate in Channel::ALL {
+            ui.selectable_value(channel, candidate, candidate.label());
+        }
+    });
+
+    // the selected channel is drawn more prominently, the others are faded.
+    let emphasis = |candidate: Channel| match *channel {
+        Channel::Rgba => true,
+        selected => selected == candidate,
+    };
+
+    ui.label(tr!("image-histogram-rgb"));
+    plot_ui(
+        ui,
+        &[
+            (&histogram.red, Color32::RED, emphasis(Channel::Red)),
+            (&histogram.green, Color32::GREEN, emphasis(Channel::Green)),
+            (&histogram.blue, Color32::from_rgb(0x40, 0x80, 0xff), emphasis(Channel::Blue)),
+        ],
+    );
+
+    ui.label(tr!("image-histogram-luminance"));
+    plot_ui(ui, &[(&histogram.luminance, Color32::GRAY, emphasis(Channel::Luminance))]);
+
+    ui.label(tr!("image-histogram-alpha"));
+    plot_ui(ui, &[(&histogram.alpha, Color32::LIGHT_GRAY, emphasis(Channel::Alpha))]);
+}
+
+fn plot_ui(ui: &mut Ui, series: &[(&[u32; BINS], Color32, bool)]) {
+    let width = ui.available_width().max(BINS as f32 / 2.0);
+    let (rect, _response) = ui.allocate_exact_size(vec2(width, PLOT_HEIGHT), Sense::hover());
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    // all series in a plot use the same scale, so they can be compared.
+    let max = series
+        .iter()
+        .flat_map(|(bins, _, _)| bins.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    for (bins, color, emphasized) in series {
+        let color = match emphasized {
+            true => *color,
+            false => color.gamma_multiply(0.25),
+        };
+
+        let points = bins
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                let x = rect.left() + rect.width() * index as f32 / (BINS - 1) as f32;
+                let y = rect.bottom() - rect.height() * *count as f32 / max as f32;
+                pos2(x, y)
+            })
+            .collect();
+
+        painter.add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+}
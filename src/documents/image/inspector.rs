@@ -0,0 +1,103 @@
+use egui::{Color32, Ui};
+use egui_i18n::tr;
+use image::RgbaImage;
+
+/// The colour of a single pixel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelSample {
+    pub position: [u32; 2],
+    /// unmultiplied sRGBA
+    pub rgba: [u8; 4],
+}
+
+impl PixelSample {
+    pub fn from_pixels(pixels: &RgbaImage, position: [u32; 2]) -> Option<Self> {
+        let [x, y] = position;
+        let pixel = pixels.get_pixel_checked(x, y)?;
+
+        Some(Self { position, rgba: pixel.0 })
+    }
+
+    /// e.g. `#FF8000FF`
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.rgba;
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+
+    pub fn color(&self) -> Color32 {
+        let [r, g, b, a] = self.rgba;
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+/// Shows the pixel under the pointer and a list of pinned samples.
+#[derive(Default)]
+pub struct PixelInspector {
+    hovered: Option<PixelSample>,
+    pinned: Vec<PixelSample>,
+}
+
+impl PixelInspector {
+    pub fn set_hovered(&mut self, sample: Option<PixelSample>) {
+        self.hovered = sample;
+    }
+
+    pub fn pin(&mut self, sample: PixelSample) {
+        // pinning the same pixel twice is not useful
+        if !self.pinned.contains(&sample) {
+            self.pinned.push(sample);
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.heading(tr!("image-inspector-heading"));
+
+        match self.hovered {
+            Some(sample) => {
+                Self::sample_ui(ui, &sample);
+            }
+            None => {
+                ui.label(tr!("image-inspector-no-pixel"));
+            }
+        }
+
+        ui.separator();
+        ui.label(tr!("image-inspector-pinned"));
+
+        let mut to_remove = None;
+        for (index, sample) in self.pinned.iter().enumerate() {
+            ui.horizontal(|ui| {
+                Self::sample_ui(ui, sample);
+                if ui.small_button("x").on_hover_text(tr!("image-inspector-unpin")).clicked() {
+                    to_remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = to_remove {
+            self.pinned.remove(index);
+        }
+
+        if !self.pinned.is_empty() && ui.button(tr!("image-inspector-clear")).clicked() {
+            self.pinned.clear();
+        }
+    }
+
+    fn sample_ui(ui: &mut Ui, sample: &PixelSample) {
+        ui.horizontal(|ui| {
+            let (rect, _response) = ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, sample.color());
+
+            let [x, y] = sample.position;
+            let [r, g, b, a] = sample.rgba;
+            ui.monospace(format!("({}, {}) rgba({}, {}, {}, {})", x, y, r, g, b, a));
+
+            if ui
+                .button(sample.to_hex())
+                .on_hover_text(tr!("image-inspector-copy-hex"))
+                .clicked()
+            {
+                ui.ctx().copy_text(sample.to_hex());
+            }
+        });
+    }
+}
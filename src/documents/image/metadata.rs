@@ -0,0 +1,145 @@
+use crate::documents::files;
+use crate::documents::image::decoder::image_reader;
+use exif::{In, Tag, Value};
+use image::metadata::Orientation;
+use image::{ExtendedColorType, ImageDecoder, ImageFormat};
+use log::debug;
+use std::path::Path;
+
+/// Information about an image file, read in the background loader.
+#[derive(Clone, Debug)]
+pub struct ImageMetadata {
+    /// the dimensions of the stored image, before any orientation is applied.
+    pub dimensions: (u32, u32),
+    pub color_type: ExtendedColorType,
+    pub format: Option<ImageFormat>,
+    pub file_size: u64,
+    /// horizontal and vertical resolution, in dots-per-inch
+    pub dpi: Option<(f64, f64)>,
+    pub orientation: Orientation,
+    pub exif: Option<ExifSummary>,
+}
+
+/// A subset of the EXIF fields, the ones that are shown to the user.
+#[derive(Clone, Debug, Default)]
+pub struct ExifSummary {
+    pub camera: Option<String>,
+    pub date_time: Option<String>,
+}
+
+impl ImageMetadata {
+    pub fn read(path: &Path) -> Option<Self> {
+        let file_size = files::len(path).ok()?;
+
+        let reader = image_reader(path).ok()?.with_guessed_format().ok()?;
+        let format = reader.format();
+
+        let mut decoder = reader.into_decoder().ok()?;
+        let dimensions = decoder.dimensions();
+        let color_type = decoder.original_color_type();
+        let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+
+        // not all formats contain EXIF data, so a failure here is not an error.
+        let exif = files::open(path)
+            .ok()
+            .and_then(|mut reader| {
+                exif::Reader::new()
+                    .read_from_container(&mut reader)
+                    .inspect_err(|cause| debug!("no exif data. path: {:?}, cause: {}", path, cause))
+                    .ok()
+            });
+
+        let dpi = exif.as_ref().and_then(exif_dpi);
+        let exif = exif.as_ref().map(|exif| ExifSummary {
+            camera: exif_camera(exif),
+            date_time: exif_string(exif, Tag::DateTimeOriginal).or_else(|| exif_string(exif, Tag::DateTime)),
+        });
+
+        Some(Self {
+            dimensions,
+            color_type,
+            format,
+            file_size,
+            dpi,
+            orientation,
+            exif,
+        })
+    }
+
+    pub fn bits_per_pixel(&self) -> u16 {
+        self.color_type.bits_per_pixel()
+    }
+
+    /// the name of the file format, e.g. `PNG`
+    pub fn format_name(&self) -> Option<String> {
+        self.format
+            .and_then(|format| format.extensions_str().first())
+            .map(|extension| extension.to_uppercase())
+    }
+}
+
+fn exif_string(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+fn exif_camera(exif: &exif::Exif) -> Option<String> {
+    let make = exif_string(exif, Tag::Make);
+    let model = exif_string(exif, Tag::Model);
+
+    match (make, model) {
+        // most cameras include the make in the model name, e.g. `Canon` + `Canon EOS 5D`
+        (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+        (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+        (make, model) => make.or(model),
+    }
+}
+
+fn exif_dpi(exif: &exif::Exif) -> Option<(f64, f64)> {
+    fn rational(exif: &exif::Exif, tag: Tag) -> Option<f64> {
+        match &exif.get_field(tag, In::PRIMARY)?.value {
+            Value::Rational(values) => values.first().map(|value| value.to_f64()),
+            _ => None,
+        }
+    }
+
+    let x = rational(exif, Tag::XResolution)?;
+    let y = rational(exif, Tag::YResolution)?;
+
+    // 2 = inches (the default), 3 = centimeters
+    let unit = exif
+        .get_field(Tag::ResolutionUnit, In::PRIMARY)
+        .and_then(|field| field.value.get_uint(0))
+        .unwrap_or(2);
+
+    match unit {
+        3 => Some((x * 2.54, y * 2.54)),
+        _ => Some((x, y)),
+    }
+}
+
+/// formats a size in bytes using binary units, e.g. `1.5 MiB`
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for candidate in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = candidate;
+    }
+
+    format!("{:.1} {}", size, unit)
+}
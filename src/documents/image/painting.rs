@@ -0,0 +1,419 @@
+use crate::documents::image::editing::ImageTool;
+use crate::documents::image::tiles::PixelRegion;
+use crate::documents::image::viewer::ImageViewerResponse;
+use egui::color_picker::{color_edit_button_srgba, Alpha};
+use egui::{pos2, Color32, DragValue, PointerButton, Pos2, Rect, Sense, Stroke, StrokeKind, Ui};
+use egui_i18n::tr;
+use image::{Rgba, RgbaImage};
+
+const MAX_BRUSH_SIZE: u32 = 64;
+
+/// A small general-purpose palette, the first entry is the default colour.
+const PALETTE: [Color32; 16] = [
+    Color32::from_rgb(0x00, 0x00, 0x00),
+    Color32::from_rgb(0xff, 0xff, 0xff),
+    Color32::from_rgb(0x7f, 0x7f, 0x7f),
+    Color32::from_rgb(0xc3, 0xc3, 0xc3),
+    Color32::from_rgb(0x88, 0x00, 0x15),
+    Color32::from_rgb(0xed, 0x1c, 0x24),
+    Color32::from_rgb(0xff, 0x7f, 0x27),
+    Color32::from_rgb(0xff, 0xf2, 0x00),
+    Color32::from_rgb(0x22, 0xb1, 0x4c),
+    Color32::from_rgb(0xb5, 0xe6, 0x1d),
+    Color32::from_rgb(0x00, 0xa2, 0xe8),
+    Color32::from_rgb(0x99, 0xd9, 0xea),
+    Color32::from_rgb(0x3f, 0x48, 0xcc),
+    Color32::from_rgb(0x70, 0x92, 0xbe),
+    Color32::from_rgb(0xa3, 0x49, 0xa4),
+    Color32::from_rgb(0xc8, 0xbf, 0xe7),
+];
+
+/// What the eraser paints with.
+const TRANSPARENT: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaintTool {
+    Pencil,
+    Eraser,
+    Fill,
+    Line,
+    Rectangle,
+}
+
+/// A completed paint action, it can be painted again, e.g. when redoing.
+#[derive(Clone, Debug)]
+pub enum PaintOperation {
+    Stroke {
+        points: Vec<[i32; 2]>,
+        size: u32,
+        color: Rgba<u8>,
+    },
+    Line {
+        from: [i32; 2],
+        to: [i32; 2],
+        size: u32,
+        color: Rgba<u8>,
+    },
+    Rectangle {
+        from: [i32; 2],
+        to: [i32; 2],
+        size: u32,
+        color: Rgba<u8>,
+    },
+    Fill {
+        position: [u32; 2],
+        color: Rgba<u8>,
+    },
+}
+
+impl PaintOperation {
+    pub fn paint(&self, pixels: &mut RgbaImage) {
+        match self {
+            PaintOperation::Stroke { points, size, color } => {
+                if let Some(first) = points.first() {
+                    stamp(pixels, *first, *size, *color);
+                }
+                for segment in points.windows(2) {
+                    line(pixels, segment[0], segment[1], *size, *color);
+                }
+            }
+            PaintOperation::Line { from, to, size, color } => {
+                line(pixels, *from, *to, *size, *color);
+            }
+            PaintOperation::Rectangle { from, to, size, color } => {
+                let corners = [*from, [to[0], from[1]], *to, [from[0], to[1]]];
+                for index in 0..corners.len() {
+                    line(pixels, corners[index], corners[(index + 1) % corners.len()], *size, *color);
+                }
+            }
+            PaintOperation::Fill { position, color } => {
+                flood_fill(pixels, *position, *color);
+            }
+        }
+    }
+}
+
+/// The result of handling paint input for a frame.
+#[derive(Default)]
+pub struct PaintOutcome {
+    /// the pixels that were modified, the textures need updating, `None` if nothing changed.
+    pub changed_region: Option<PixelRegion>,
+    /// set when an operation was completed, along with the pixels from before the operation.
+    pub completed: Option<(PaintOperation, RgbaImage)>,
+}
+
+struct ActivePaint {
+    /// the pixels before the operation was started, only taken once the pixels are about to be changed.
+    previous: Option<RgbaImage>,
+    operation: PaintOperation,
+}
+
+pub struct PaintToolbox {
+    color: Color32,
+    brush_size: u32,
+    active: Option<ActivePaint>,
+}
+
+impl Default for PaintToolbox {
+    fn default() -> Self {
+        Self {
+            color: PALETTE[0],
+            brush_size: 1,
+            active: None,
+        }
+    }
+}
+
+impl PaintToolbox {
+    fn color(&self) -> Rgba<u8> {
+        Rgba(self.color.to_srgba_unmultiplied())
+    }
+
+    /// Shows the tools, brush size and palette, selecting a tool changes `tool`.
+    pub fn ui(&mut self, ui: &mut Ui, tool: &mut ImageTool) {
+        ui.heading(tr!("image-paint-heading"));
+
+        ui.horizontal_wrapped(|ui| {
+            let tools = [
+                (PaintTool::Pencil, tr!("image-paint-tool-pencil")),
+                (PaintTool::Eraser, tr!("image-paint-tool-eraser")),
+                (PaintTool::Fill, tr!("image-paint-tool-fill")),
+                (PaintTool::Line, tr!("image-paint-tool-line")),
+                (PaintTool::Rectangle, tr!("image-paint-tool-rectangle")),
+            ];
+            for (paint_tool, label) in tools {
+                ui.selectable_value(tool, ImageTool::Paint(paint_tool), label);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr!("image-paint-brush-size"));
+            ui.add(DragValue::new(&mut self.brush_size).range(1..=MAX_BRUSH_SIZE));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(tr!("image-paint-color"));
+            color_edit_button_srgba(ui, &mut self.color, Alpha::OnlyBlend);
+        });
+
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+            for color in PALETTE {
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(16.0, 16.0), Sense::click());
+                ui.painter().rect_filled(rect, 0.0, color);
+                if color == self.color {
+                    ui.painter()
+                        .rect_stroke(rect, 0.0, ui.visuals().selection.stroke, StrokeKind::Outside);
+                }
+                if response.clicked() {
+                    self.color = color;
+                }
+            }
+        });
+    }
+
+    /// Handles drawing with `tool`, call with the response from the viewer.
+    pub fn input(
+        &mut self,
+        tool: PaintTool,
+        ui: &Ui,
+        viewer_response: &ImageViewerResponse,
+        pixels: &mut RgbaImage,
+    ) -> PaintOutcome {
+        let response = &viewer_response.response;
+        let texture_pos = |pos: Pos2| {
+            let texture_pos = viewer_response.texture_pos_at(pos);
+            [texture_pos.x.floor() as i32, texture_pos.y.floor() as i32]
+        };
+
+        let mut outcome = PaintOutcome::default();
+
+        match tool {
+            PaintTool::Pencil | PaintTool::Eraser => {
+                let color = match tool {
+                    PaintTool::Eraser => TRANSPARENT,
+                    _ => self.color(),
+                };
+
+                if response.drag_started_by(PointerButton::Primary) || response.clicked_by(PointerButton::Primary) {
+                    if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
+                        let point = texture_pos(origin);
+                        let previous = pixels.clone();
+                        stamp(pixels, point, self.brush_size, color);
+                        outcome.changed_region = Some(brush_region(point, point, self.brush_size));
+
+                        self.active = Some(ActivePaint {
+                            previous: Some(previous),
+                            operation: PaintOperation::Stroke {
+                                points: vec![point],
+                                size: self.brush_size,
+                                color,
+                            },
+                        });
+                    }
+                }
+
+                if response.dragged_by(PointerButton::Primary) {
+                    let current = response.interact_pointer_pos().map(texture_pos);
+
+                    if let (Some(current), Some(ActivePaint { operation: PaintOperation::Stroke { points, size, color }, .. })) =
+                        (current, &mut self.active)
+                    {
+                        let last = *points.last().unwrap();
+                        if last != current {
+                            line(pixels, last, current, *size, *color);
+                            points.push(current);
+                            outcome.changed_region = Some(brush_region(last, current, *size));
+                        }
+                    }
+                }
+            }
+            PaintTool::Fill => {
+                if response.clicked_by(PointerButton::Primary) {
+                    if let Some(position) = viewer_response.hovered_pixel() {
+                        let previous = pixels.clone();
+                        let operation = PaintOperation::Fill {
+                            position,
+                            color: self.color(),
+                        };
+                        operation.paint(pixels);
+                        outcome.changed_region = Some(whole_region(pixels));
+                        outcome.completed = Some((operation, previous));
+                    }
+                }
+            }
+            PaintTool::Line | PaintTool::Rectangle => {
+                if response.drag_started_by(PointerButton::Primary) {
+                    if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
+                        let from = texture_pos(origin);
+                        let (size, color) = (self.brush_size, self.color());
+                        let operation = match tool {
+                            PaintTool::Line => PaintOperation::Line { from, to: from, size, color },
+                            _ => PaintOperation::Rectangle { from, to: from, size, color },
+                        };
+                        self.active = Some(ActivePaint {
+                            previous: None,
+                            operation,
+                        });
+                    }
+                }
+
+                if response.dragged_by(PointerButton::Primary) {
+                    let current = response.interact_pointer_pos().map(texture_pos);
+
+                    if let (Some(current), Some(active)) = (current, &mut self.active) {
+                        if let PaintOperation::Line { to, .. } | PaintOperation::Rectangle { to, .. } =
+                            &mut active.operation
+                        {
+                            *to = current;
+                        }
+                    }
+                }
+
+                // the shape is only painted into the pixels when the drag is complete, until then a preview is shown.
+                if response.drag_stopped_by(PointerButton::Primary) {
+                    if let Some(active) = &mut self.active {
+                        active.previous = Some(pixels.clone());
+                        active.operation.paint(pixels);
+                        outcome.changed_region = Some(whole_region(pixels));
+                    }
+                }
+            }
+        }
+
+        if response.drag_stopped_by(PointerButton::Primary) || response.clicked_by(PointerButton::Primary) {
+            if let Some(ActivePaint {
+                previous: Some(previous),
+                operation,
+            }) = self.active.take()
+            {
+                outcome.completed = Some((operation, previous));
+            }
+        }
+
+        outcome
+    }
+
+    /// Shows a preview of the line or rectangle being drawn.
+    pub fn paint_preview(&self, ui: &Ui, viewer_response: &ImageViewerResponse) {
+        let Some(active) = &self.active else {
+            return;
+        };
+
+        // the center of a texture pixel, on screen
+        let center = |point: [i32; 2]| viewer_response.screen_pos_of(pos2(point[0] as f32 + 0.5, point[1] as f32 + 0.5));
+
+        let painter = ui.painter_at(viewer_response.response.rect);
+        match &active.operation {
+            PaintOperation::Line { from, to, size, color } => {
+                let stroke = Stroke::new(*size as f32 * viewer_response.pixel_size(), to_color32(*color));
+                painter.line_segment([center(*from), center(*to)], stroke);
+            }
+            PaintOperation::Rectangle { from, to, size, color } => {
+                let stroke = Stroke::new(*size as f32 * viewer_response.pixel_size(), to_color32(*color));
+                painter.rect_stroke(Rect::from_two_pos(center(*from), center(*to)), 0.0, stroke, StrokeKind::Middle);
+            }
+            PaintOperation::Stroke { .. } | PaintOperation::Fill { .. } => {}
+        }
+    }
+}
+
+fn to_color32(color: Rgba<u8>) -> Color32 {
+    let [r, g, b, a] = color.0;
+    Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// the pixels changed by stamping the brush along the line, which may extend beyond the image.
+fn brush_region(from: [i32; 2], to: [i32; 2], size: u32) -> PixelRegion {
+    // the same extent as `stamp`
+    let extent = ((size as f32 - 1.0) / 2.0 + 0.5).ceil() as i32;
+    let min = |axis: usize| (from[axis].min(to[axis]) - extent).max(0) as u32;
+    let max = |axis: usize| (from[axis].max(to[axis]) + extent + 1).max(0) as u32;
+
+    PixelRegion {
+        min: [min(0), min(1)],
+        max: [max(0), max(1)],
+    }
+}
+
+fn whole_region(pixels: &RgbaImage) -> PixelRegion {
+    PixelRegion {
+        min: [0, 0],
+        max: [pixels.width(), pixels.height()],
+    }
+}
+
+/// paints a round brush of diameter `size`, centered on `center`, pixels outside the image are ignored.
+fn stamp(pixels: &mut RgbaImage, center: [i32; 2], size: u32, color: Rgba<u8>) {
+    let radius = (size as f32 - 1.0) / 2.0 + 0.5;
+    let extent = radius.ceil() as i32;
+
+    for dy in -extent..=extent {
+        for dx in -extent..=extent {
+            if (dx * dx + dy * dy) as f32 > radius * radius {
+                continue;
+            }
+            let (x, y) = (center[0] + dx, center[1] + dy);
+            if x >= 0 && y >= 0 && (x as u32) < pixels.width() && (y as u32) < pixels.height() {
+                pixels.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// Bresenham's line algorithm, stamping the brush at each point.
+fn line(pixels: &mut RgbaImage, from: [i32; 2], to: [i32; 2], size: u32, color: Rgba<u8>) {
+    let (mut x, mut y) = (from[0], from[1]);
+    let (dx, dy) = ((to[0] - x).abs(), -(to[1] - y).abs());
+    let (step_x, step_y) = (if x < to[0] { 1 } else { -1 }, if y < to[1] { 1 } else { -1 });
+    let mut error = dx + dy;
+
+    loop {
+        stamp(pixels, [x, y], size, color);
+        if x == to[0] && y == to[1] {
+            break;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Replaces the contiguous (4-connected) area of pixels that have the same colour as the pixel at `position`.
+fn flood_fill(pixels: &mut RgbaImage, position: [u32; 2], color: Rgba<u8>) {
+    let [x, y] = position;
+    let Some(target) = pixels.get_pixel_checked(x, y).copied() else {
+        return;
+    };
+    if target == color {
+        return;
+    }
+
+    let (width, height) = pixels.dimensions();
+    let mut stack = vec![(x, y)];
+    while let Some((x, y)) = stack.pop() {
+        if *pixels.get_pixel(x, y) != target {
+            continue;
+        }
+        pixels.put_pixel(x, y, color);
+
+        if x > 0 {
+            stack.push((x - 1, y));
+        }
+        if x + 1 < width {
+            stack.push((x + 1, y));
+        }
+        if y > 0 {
+            stack.push((x, y - 1));
+        }
+        if y + 1 < height {
+            stack.push((x, y + 1));
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::documents::files;
+use crate::documents::image::decoder::{check_limits, DecodeError, DecoderSettings};
+use image::{Rgba, RgbaImage};
+use log::error;
+use resvg::{tiny_skia, usvg};
+use std::path::Path;
+
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// renders the SVG at its natural size, which is checked against the limits before any pixels are allocated.
+pub fn rasterize(path: &Path, settings: &DecoderSettings) -> Result<RgbaImage, DecodeError> {
+    let data = files::read(path)?;
+
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default())
+        .inspect_err(|cause| error!("Failed to parse svg. path: {:?}, cause: {}", path, cause))
+        .map_err(|cause| DecodeError::Loader(cause.to_string()))?;
+
+    let size = tree.size().to_int_size();
+    check_limits(size.width(), size.height(), settings)?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| DecodeError::Loader("invalid svg size".to_string()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    // the pixmap is premultiplied, the rest of the image code expects unmultiplied pixels.
+    let mut pixels = RgbaImage::new(size.width(), size.height());
+    for (pixel, premultiplied) in pixels.pixels_mut().zip(pixmap.pixels()) {
+        let color = premultiplied.demultiply();
+        *pixel = Rgba([color.red(), color.green(), color.blue(), color.alpha()]);
+    }
+
+    Ok(pixels)
+}
@@ -0,0 +1,310 @@
+use crate::documents::image::viewer::ImageViewerResponse;
+use egui::load::SizedTexture;
+use egui::{pos2, vec2, Color32, ColorImage, Context, Rect, TextureHandle, TextureId, TextureOptions, Ui};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use log::debug;
+use std::collections::HashMap;
+
+/// the width and height of the full-resolution tiles, in pixels, unless `max_texture_side` is smaller.
+const TILE_SIZE: u32 = 1024;
+
+/// uploading tiles is expensive, the remaining tiles are uploaded on the next frames.
+const MAX_TILE_UPLOADS_PER_FRAME: usize = 4;
+
+/// downscaling a large image for the preview is expensive, while painting it's rebuilt at most this often, in seconds.
+const PREVIEW_REBUILD_INTERVAL: f64 = 0.25;
+
+/// A rectangle of pixels, e.g. the pixels changed by painting, `max` is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelRegion {
+    pub min: [u32; 2],
+    pub max: [u32; 2],
+}
+
+impl PixelRegion {
+    /// `None` if the region is entirely outside the image.
+    fn clamp(self, pixels: &RgbaImage) -> Option<Self> {
+        let (width, height) = pixels.dimensions();
+        let min = [self.min[0].min(width), self.min[1].min(height)];
+        let max = [self.max[0].min(width), self.max[1].min(height)];
+
+        (min[0] < max[0] && min[1] < max[1]).then_some(Self { min, max })
+    }
+
+    fn intersect(self, other: Self) -> Option<Self> {
+        let min = [self.min[0].max(other.min[0]), self.min[1].max(other.min[1])];
+        let max = [self.max[0].min(other.max[0]), self.max[1].min(other.max[1])];
+
+        (min[0] < max[0] && min[1] < max[1]).then_some(Self { min, max })
+    }
+
+    fn crop(&self, pixels: &RgbaImage) -> ColorImage {
+        let tile = imageops::crop_imm(
+            pixels,
+            self.min[0],
+            self.min[1],
+            self.max[0] - self.min[0],
+            self.max[1] - self.min[1],
+        )
+        .to_image();
+
+        color_image_from_pixels(&tile)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct TileKey {
+    column: u32,
+    row: u32,
+    nearest: bool,
+}
+
+/// The textures for an image.
+///
+/// Images that fit within `max_texture_side` use a single texture. Larger images use a downscaled preview, and
+/// full-resolution tiles are created for the visible region when zoomed-in beyond the resolution of the preview.
+pub struct ImageTextures {
+    name: String,
+    max_texture_side: u32,
+    texture_handle: TextureHandle,
+    /// the scale of the preview relative to the image, `None` if the texture is full-resolution.
+    preview_scale: Option<f32>,
+    /// created on demand, used at high zoom levels, only for full-resolution textures.
+    nearest_texture_handle: Option<TextureHandle>,
+    tiles: HashMap<TileKey, TextureHandle>,
+    /// the preview does not show some changes yet, see `update_region`.
+    preview_stale: bool,
+    /// `InputState::time` when the preview was last built.
+    preview_time: f64,
+}
+
+impl ImageTextures {
+    /// can be called from a background thread, `max_texture_side` must be read on the ui thread, see
+    /// `max_texture_side`, as the input state is only valid there.
+    pub fn new(ctx: &Context, name: String, pixels: &RgbaImage, max_texture_side: usize) -> Self {
+        let max_texture_side = max_texture_side as u32;
+
+        let (color_image, preview_scale) = Self::color_image(pixels, max_texture_side);
+        let texture_handle = ctx.load_texture(name.as_str(), color_image, TextureOptions::default());
+
+        Self {
+            name,
+            max_texture_side,
+            texture_handle,
+            preview_scale,
+            nearest_texture_handle: None,
+            tiles: HashMap::new(),
+            preview_stale: false,
+            preview_time: 0.0,
+        }
+    }
+
+    /// call on the ui thread, pass the result to `new`.
+    pub fn max_texture_side(ctx: &Context) -> usize {
+        ctx.input(|input| input.max_texture_side)
+    }
+
+    fn color_image(pixels: &RgbaImage, max_texture_side: u32) -> (ColorImage, Option<f32>) {
+        let (width, height) = pixels.dimensions();
+        let largest_side = width.max(height);
+
+        if largest_side <= max_texture_side {
+            return (color_image_from_pixels(pixels), None);
+        }
+
+        let scale = max_texture_side as f32 / largest_side as f32;
+        let preview_width = ((width as f32 * scale) as u32).clamp(1, max_texture_side);
+        let preview_height = ((height as f32 * scale) as u32).clamp(1, max_texture_side);
+        debug!(
+            "image exceeds max texture side, using a preview. size: {}x{}, preview: {}x{}",
+            width, height, preview_width, preview_height
+        );
+
+        let preview = imageops::resize(pixels, preview_width, preview_height, FilterType::Triangle);
+
+        (color_image_from_pixels(&preview), Some(scale))
+    }
+
+    pub fn id(&self) -> TextureId {
+        self.texture_handle.id()
+    }
+
+    /// the texture, sized as the full-resolution image, regardless of whether it is a preview.
+    pub fn texture(&self, pixels: &RgbaImage) -> SizedTexture {
+        let (width, height) = pixels.dimensions();
+        SizedTexture::new(self.texture_handle.id(), vec2(width as f32, height as f32))
+    }
+
+    /// `None` for previews, tiles are used instead, see `paint_tiles`.
+    pub fn nearest_texture(&mut self, ctx: &Context, pixels: &RgbaImage) -> Option<SizedTexture> {
+        if self.preview_scale.is_some() {
+            return None;
+        }
+
+        let name = &self.name;
+        let handle = self.nearest_texture_handle.get_or_insert_with(|| {
+            let name = format!("{}#nearest", name);
+            ctx.load_texture(name, color_image_from_pixels(pixels), TextureOptions::NEAREST)
+        });
+
+        let (width, height) = pixels.dimensions();
+        Some(SizedTexture::new(handle.id(), vec2(width as f32, height as f32)))
+    }
+
+    /// call after the pixels have been changed, e.g. the image may now need a preview, or no longer need one.
+    pub fn update(&mut self, pixels: &RgbaImage) {
+        let (color_image, preview_scale) = Self::color_image(pixels, self.max_texture_side);
+        self.texture_handle.set(color_image, TextureOptions::default());
+        self.preview_scale = preview_scale;
+
+        // re-created on demand
+        self.nearest_texture_handle = None;
+        self.tiles.clear();
+        self.preview_stale = false;
+    }
+
+    /// call after the pixels in `region` have been changed, e.g. on each frame while painting, the size of the
+    /// image must not have changed, use `update` for that.
+    ///
+    /// only the changed region of full-resolution textures and tiles is uploaded, a downscaled preview is rebuilt at
+    /// most every `PREVIEW_REBUILD_INTERVAL`, call `update` when done to bring it up to date.
+    pub fn update_region(&mut self, ctx: &Context, pixels: &RgbaImage, region: PixelRegion) {
+        let Some(region) = region.clamp(pixels) else {
+            return;
+        };
+        let position = [region.min[0] as usize, region.min[1] as usize];
+
+        if self.preview_scale.is_none() {
+            let color_image = region.crop(pixels);
+            if let Some(nearest_texture_handle) = &mut self.nearest_texture_handle {
+                nearest_texture_handle.set_partial(position, color_image.clone(), TextureOptions::NEAREST);
+            }
+            self.texture_handle
+                .set_partial(position, color_image, TextureOptions::default());
+            return;
+        }
+
+        let tile_size = self.tile_size();
+        for (key, tile) in self.tiles.iter_mut() {
+            let tile_region = PixelRegion {
+                min: [key.column * tile_size, key.row * tile_size],
+                max: [(key.column + 1) * tile_size, (key.row + 1) * tile_size],
+            };
+            let Some(changed) = tile_region.intersect(region) else {
+                continue;
+            };
+
+            let options = match key.nearest {
+                true => TextureOptions::NEAREST,
+                false => TextureOptions::default(),
+            };
+            let position = [
+                (changed.min[0] - tile_region.min[0]) as usize,
+                (changed.min[1] - tile_region.min[1]) as usize,
+            ];
+            tile.set_partial(position, changed.crop(pixels), options);
+        }
+
+        self.preview_stale = true;
+        self.rebuild_stale_preview(ctx, pixels);
+    }
+
+    /// rebuilds the preview if it's stale, unless it was rebuilt recently, in which case a repaint is requested
+    /// so it's rebuilt later, even if nothing else changes.
+    fn rebuild_stale_preview(&mut self, ctx: &Context, pixels: &RgbaImage) {
+        if !self.preview_stale {
+            return;
+        }
+
+        let time = ctx.input(|input| input.time);
+        let elapsed = time - self.preview_time;
+        if elapsed < PREVIEW_REBUILD_INTERVAL {
+            ctx.request_repaint_after_secs((PREVIEW_REBUILD_INTERVAL - elapsed) as f32);
+            return;
+        }
+
+        let (color_image, _preview_scale) = Self::color_image(pixels, self.max_texture_side);
+        self.texture_handle.set(color_image, TextureOptions::default());
+        self.preview_stale = false;
+        self.preview_time = time;
+    }
+
+    fn tile_size(&self) -> u32 {
+        TILE_SIZE.min(self.max_texture_side)
+    }
+
+    /// paints full-resolution tiles over the visible part of the preview, when the preview's resolution is too low.
+    pub fn paint_tiles(&mut self, ui: &Ui, viewer_response: &ImageViewerResponse, pixels: &RgbaImage, nearest: bool) {
+        let Some(preview_scale) = self.preview_scale else {
+            return;
+        };
+        self.rebuild_stale_preview(ui.ctx(), pixels);
+
+        // the number of screen pixels per image pixel
+        let screen_scale = viewer_response.pixel_size() * ui.ctx().pixels_per_point();
+        if screen_scale <= preview_scale {
+            // the preview has enough detail
+            self.tiles.clear();
+            return;
+        }
+
+        let viewport = viewer_response.response.rect;
+        let visible = viewer_response.image_rect.intersect(viewport);
+        if !visible.is_positive() {
+            return;
+        }
+
+        let (width, height) = pixels.dimensions();
+        let tile_size = self.tile_size();
+
+        let min = viewer_response.texture_pos_at(visible.min);
+        let max = viewer_response.texture_pos_at(visible.max);
+        let columns = (min.x.max(0.0) as u32 / tile_size)..=((max.x as u32).min(width - 1) / tile_size);
+        let rows = (min.y.max(0.0) as u32 / tile_size)..=((max.y as u32).min(height - 1) / tile_size);
+
+        let painter = ui.painter_at(viewport);
+        let mut visible_keys = Vec::new();
+        let mut uploads = 0;
+
+        for row in rows {
+            for column in columns.clone() {
+                let key = TileKey { column, row, nearest };
+                let x = column * tile_size;
+                let y = row * tile_size;
+                let tile_width = tile_size.min(width - x);
+                let tile_height = tile_size.min(height - y);
+
+                if !self.tiles.contains_key(&key) {
+                    if uploads >= MAX_TILE_UPLOADS_PER_FRAME {
+                        ui.ctx().request_repaint();
+                        continue;
+                    }
+                    uploads += 1;
+
+                    let tile = imageops::crop_imm(pixels, x, y, tile_width, tile_height).to_image();
+                    let options = match nearest {
+                        true => TextureOptions::NEAREST,
+                        false => TextureOptions::default(),
+                    };
+                    let name = format!("{}#tile-{}-{}", self.name, column, row);
+                    self.tiles
+                        .insert(key, ui.ctx().load_texture(name, color_image_from_pixels(&tile), options));
+                }
+                visible_keys.push(key);
+
+                let rect = viewer_response.screen_rect_of_pixels([x, y], [x + tile_width, y + tile_height]);
+                let uv = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+                painter.image(self.tiles[&key].id(), rect, uv, Color32::WHITE);
+            }
+        }
+
+        // free the textures of tiles that are no longer visible
+        self.tiles.retain(|key, _| visible_keys.contains(key));
+    }
+}
+
+fn color_image_from_pixels(pixels: &RgbaImage) -> ColorImage {
+    let size = [pixels.width() as usize, pixels.height() as usize];
+    ColorImage::from_rgba_unmultiplied(size, pixels.as_raw())
+}
@@ -0,0 +1,386 @@
+use egui::epaint::Vertex;
+use egui::load::SizedTexture;
+use egui_i18n::tr;
+use egui::{pos2, vec2, Color32, Key, Mesh, PointerButton, Pos2, Rect, Response, Sense, Shape, Ui, Vec2};
+use image::metadata::Orientation;
+
+const ZOOM_MIN: f32 = 0.01;
+const ZOOM_MAX: f32 = 64.0;
+
+/// multiplier used for each zoom in/out step (toolbar buttons and keyboard)
+const ZOOM_STEP: f32 = 1.25;
+
+/// points of scroll required to double/halve the zoom
+const SCROLL_POINTS_PER_DOUBLING: f32 = 200.0;
+
+/// at or above this zoom level nearest-neighbour sampling is used, so individual pixels can be inspected.
+pub const NEAREST_SAMPLING_ZOOM: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZoomMode {
+    /// scale the image so that it fits entirely in the available space, preserving the aspect ratio.
+    Fit,
+    /// a fixed zoom level, where `1.0` is actual size (one image pixel per screen pixel).
+    Custom(f32),
+}
+
+/// Viewer state for a single image, zoom, pan and fit mode.
+///
+/// The pan is stored as the offset of the image center from the center of the viewport, in points.
+pub struct ImageViewer {
+    mode: ZoomMode,
+    pan: Vec2,
+
+    /// the effective zoom used for the last frame, also valid in `ZoomMode::Fit`.
+    zoom: f32,
+
+    /// when `false` the primary button is left for the caller, e.g. for a selection or drawing tool.
+    /// the middle and secondary buttons always pan.
+    primary_drag_pans: bool,
+}
+
+impl Default for ImageViewer {
+    fn default() -> Self {
+        Self {
+            mode: ZoomMode::Fit,
+            pan: Vec2::ZERO,
+            zoom: 1.0,
+            primary_drag_pans: true,
+        }
+    }
+}
+
+impl ImageViewer {
+    /// the effective zoom, where `1.0` is 100%.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn mode(&self) -> ZoomMode {
+        self.mode
+    }
+
+    /// `true` if the texture should be drawn using nearest-neighbour sampling.
+    pub fn wants_nearest_sampling(&self) -> bool {
+        self.zoom >= NEAREST_SAMPLING_ZOOM
+    }
+
+    pub fn set_primary_drag_pans(&mut self, primary_drag_pans: bool) {
+        self.primary_drag_pans = primary_drag_pans;
+    }
+
+    pub fn fit(&mut self) {
+        self.mode = ZoomMode::Fit;
+        self.pan = Vec2::ZERO;
+    }
+
+    pub fn actual_size(&mut self) {
+        self.mode = ZoomMode::Custom(1.0);
+        self.pan = Vec2::ZERO;
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom_by(ZOOM_STEP, Vec2::ZERO);
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom_by(1.0 / ZOOM_STEP, Vec2::ZERO);
+    }
+
+    /// the zoom buttons, for use in a toolbar.
+    pub fn zoom_controls_ui(&mut self, ui: &mut Ui) {
+        if ui
+            .selectable_label(self.mode == ZoomMode::Fit, tr!("image-toolbar-zoom-fit"))
+            .clicked()
+        {
+            self.fit();
+        }
+        if ui
+            .selectable_label(self.mode == ZoomMode::Custom(1.0), tr!("image-toolbar-zoom-actual-size"))
+            .clicked()
+        {
+            self.actual_size();
+        }
+        if ui.button(tr!("image-toolbar-zoom-out")).clicked() {
+            self.zoom_out();
+        }
+        if ui.button(tr!("image-toolbar-zoom-in")).clicked() {
+            self.zoom_in();
+        }
+        ui.label(format!("{:.0}%", self.zoom * 100.0));
+    }
+
+    /// zoom by `factor`, keeping the point at `anchor` (relative to the viewport center) stationary.
+    fn zoom_by(&mut self, factor: f32, anchor: Vec2) {
+        let old_zoom = self.zoom;
+        let new_zoom = (old_zoom * factor).clamp(ZOOM_MIN, ZOOM_MAX);
+
+        self.pan = anchor - (anchor - self.pan) * (new_zoom / old_zoom);
+        self.zoom = new_zoom;
+        self.mode = ZoomMode::Custom(new_zoom);
+    }
+
+    /// Shows the image, filling the available space, and handles zoom and pan input.
+    ///
+    /// `nearest_texture`, if provided, is used instead of `texture` when `wants_nearest_sampling` returns true.
+    /// `orientation` is applied when drawing, the texture itself is not modified.
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        texture: SizedTexture,
+        nearest_texture: Option<SizedTexture>,
+        orientation: Orientation,
+    ) -> ImageViewerResponse {
+        let (viewport, response) = ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+
+        if response.clicked() {
+            response.request_focus();
+        }
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        // the size of the image, in points, at 100%
+        let actual_size = oriented_size(texture.size, orientation) / pixels_per_point;
+
+        self.update_zoom((viewport.size() / actual_size).min_elem());
+
+        self.handle_input(ui, &response, viewport);
+
+        let image_rect = Rect::from_center_size(viewport.center() + self.pan, actual_size * self.zoom);
+
+        let texture_id = match nearest_texture {
+            Some(nearest_texture) if self.wants_nearest_sampling() => nearest_texture.id,
+            _ => texture.id,
+        };
+
+        let painter = ui.painter_at(viewport);
+        painter.add(oriented_image_shape(texture_id, image_rect, orientation));
+
+        ImageViewerResponse {
+            response,
+            image_rect,
+            texture_size: texture.size,
+            orientation,
+        }
+    }
+
+    /// Shows two images next to each other, with the same zoom and pan, e.g. to compare them.
+    ///
+    /// the input is handled once, for both images, so dragging or zooming either image moves both.
+    pub fn ui_side_by_side(&mut self, ui: &mut Ui, first: SizedTexture, second: SizedTexture) {
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click_and_drag());
+
+        if response.clicked() {
+            response.request_focus();
+        }
+
+        let gap = ui.spacing().item_spacing.x;
+        let viewport_size = vec2(((rect.width() - gap) / 2.0).max(0.0), rect.height());
+        let viewports = [
+            Rect::from_min_size(rect.min, viewport_size),
+            Rect::from_min_size(pos2(rect.max.x - viewport_size.x, rect.min.y), viewport_size),
+        ];
+
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let actual_sizes = [first.size / pixels_per_point, second.size / pixels_per_point];
+
+        // both images fit, when they are different sizes.
+        let fit_zoom = actual_sizes
+            .iter()
+            .map(|actual_size| (viewport_size / *actual_size).min_elem())
+            .fold(f32::INFINITY, f32::min);
+        self.update_zoom(fit_zoom);
+
+        // zooming is anchored to the pointer, relative to the viewport it's over.
+        let input_viewport = match response.hover_pos() {
+            Some(pos) if viewports[1].contains(pos) => viewports[1],
+            _ => viewports[0],
+        };
+        self.handle_input(ui, &response, input_viewport);
+
+        for ((texture, actual_size), viewport) in [first, second].into_iter().zip(actual_sizes).zip(viewports) {
+            let image_rect = Rect::from_center_size(viewport.center() + self.pan, actual_size * self.zoom);
+            ui.painter_at(viewport)
+                .add(oriented_image_shape(texture.id, image_rect, Orientation::NoTransforms));
+        }
+    }
+
+    /// applies the zoom mode, `fit_zoom` is the zoom that fits the image in the viewport.
+    fn update_zoom(&mut self, fit_zoom: f32) {
+        match self.mode {
+            ZoomMode::Fit => {
+                if fit_zoom.is_finite() && fit_zoom > 0.0 {
+                    self.zoom = fit_zoom;
+                }
+            }
+            ZoomMode::Custom(zoom) => self.zoom = zoom,
+        }
+    }
+
+    fn handle_input(&mut self, ui: &Ui, response: &Response, viewport: Rect) {
+        let panning = (self.primary_drag_pans && response.dragged_by(PointerButton::Primary))
+            || response.dragged_by(PointerButton::Middle)
+            || response.dragged_by(PointerButton::Secondary);
+
+        if panning {
+            self.pan += response.drag_delta();
+            self.mode = ZoomMode::Custom(self.zoom);
+        }
+
+        if response.hovered() {
+            let (scroll_delta, zoom_delta, hover_pos) = ui.input(|i| {
+                (i.smooth_scroll_delta.y, i.zoom_delta(), i.pointer.hover_pos())
+            });
+
+            let anchor = hover_pos.map_or(Vec2::ZERO, |pos| pos - viewport.center());
+
+            if scroll_delta != 0.0 {
+                self.zoom_by(2.0_f32.powf(scroll_delta / SCROLL_POINTS_PER_DOUBLING), anchor);
+            }
+            if zoom_delta != 1.0 {
+                self.zoom_by(zoom_delta, anchor);
+            }
+        }
+
+        if response.hovered() || response.has_focus() {
+            ui.input(|i| {
+                if i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals) {
+                    self.zoom_in();
+                }
+                if i.key_pressed(Key::Minus) {
+                    self.zoom_out();
+                }
+                if i.key_pressed(Key::Num0) {
+                    self.fit();
+                }
+                if i.key_pressed(Key::Num1) {
+                    self.actual_size();
+                }
+            });
+        }
+    }
+}
+
+pub struct ImageViewerResponse {
+    pub response: Response,
+    /// the screen-space rectangle the image was drawn into, may extend beyond the viewport.
+    pub image_rect: Rect,
+    texture_size: Vec2,
+    orientation: Orientation,
+}
+
+impl ImageViewerResponse {
+    /// The texture pixel at the given screen position, or `None` if the position is outside the image.
+    pub fn pixel_at(&self, pos: Pos2) -> Option<[u32; 2]> {
+        if !self.image_rect.contains(pos) || self.texture_size.min_elem() < 1.0 {
+            return None;
+        }
+
+        let texture_pos = self.texture_pos_at(pos);
+
+        let x = (texture_pos.x as u32).min(self.texture_size.x as u32 - 1);
+        let y = (texture_pos.y as u32).min(self.texture_size.y as u32 - 1);
+
+        Some([x, y])
+    }
+
+    /// The position in the texture, in texture pixels, of the given screen position.
+    ///
+    /// The position is not limited to the image, e.g. for drawing tools that are dragged beyond its edges.
+    pub fn texture_pos_at(&self, pos: Pos2) -> Pos2 {
+        let normalized = (pos - self.image_rect.min) / self.image_rect.size();
+        let [top_left, top_right, _bottom_right, bottom_left] = orientation_uvs(self.orientation);
+        let uv = top_left + (top_right - top_left) * normalized.x + (bottom_left - top_left) * normalized.y;
+
+        (uv.to_vec2() * self.texture_size).to_pos2()
+    }
+
+    /// The size of a texture pixel on screen, in points.
+    pub fn pixel_size(&self) -> f32 {
+        let size = oriented_size(self.texture_size, self.orientation);
+        self.image_rect.width() / size.x
+    }
+
+    /// Like `pixel_at`, but positions outside the image are clamped to the nearest edge pixel.
+    pub fn pixel_at_clamped(&self, pos: Pos2) -> Option<[u32; 2]> {
+        let clamped = pos.clamp(self.image_rect.min, self.image_rect.max);
+        self.pixel_at(clamped)
+    }
+
+    /// The screen-space rectangle covering the texture pixels from `min` (inclusive) to `max` (exclusive).
+    pub fn screen_rect_of_pixels(&self, min: [u32; 2], max: [u32; 2]) -> Rect {
+        let a = self.screen_pos_of(pos2(min[0] as f32, min[1] as f32));
+        let b = self.screen_pos_of(pos2(max[0] as f32, max[1] as f32));
+        Rect::from_two_pos(a, b)
+    }
+
+    /// The screen position of a position in the texture, in texture pixels.
+    pub fn screen_pos_of(&self, texture_pos: Pos2) -> Pos2 {
+        let uv = texture_pos.to_vec2() / self.texture_size;
+
+        // inverse of the mapping in `pixel_at`, the orientations only ever swap and/or mirror the axes.
+        let [top_left, top_right, _bottom_right, bottom_left] = orientation_uvs(self.orientation);
+        let (dx, dy) = (top_right - top_left, bottom_left - top_left);
+        let normalized = if dx.x != 0.0 {
+            vec2((uv.x - top_left.x) / dx.x, (uv.y - top_left.y) / dy.y)
+        } else {
+            vec2((uv.y - top_left.y) / dx.y, (uv.x - top_left.x) / dy.x)
+        };
+
+        self.image_rect.min + normalized * self.image_rect.size()
+    }
+
+    /// The texture pixel under the pointer, if any.
+    pub fn hovered_pixel(&self) -> Option<[u32; 2]> {
+        self.response
+            .hover_pos()
+            .and_then(|pos| self.pixel_at(pos))
+    }
+}
+
+/// the size of the image after applying the orientation.
+fn oriented_size(size: Vec2, orientation: Orientation) -> Vec2 {
+    match orientation {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => vec2(size.y, size.x),
+        Orientation::NoTransforms
+        | Orientation::Rotate180
+        | Orientation::FlipHorizontal
+        | Orientation::FlipVertical => size,
+    }
+}
+
+/// a textured rectangle, where the texture coordinates are arranged to apply the orientation.
+fn oriented_image_shape(texture_id: egui::TextureId, rect: Rect, orientation: Orientation) -> Shape {
+    let corners: [Pos2; 4] = [rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom()];
+
+    let mut mesh = Mesh::with_texture(texture_id);
+    for (pos, uv) in corners.into_iter().zip(orientation_uvs(orientation)) {
+        mesh.vertices.push(Vertex {
+            pos,
+            uv,
+            color: Color32::WHITE,
+        });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(0, 2, 3);
+
+    Shape::mesh(mesh)
+}
+
+/// texture coordinates for the top-left, top-right, bottom-right and bottom-left corners of the displayed image.
+fn orientation_uvs(orientation: Orientation) -> [Pos2; 4] {
+    let uvs: [(f32, f32); 4] = match orientation {
+        Orientation::NoTransforms => [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+        Orientation::Rotate90 => [(0.0, 1.0), (0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+        Orientation::Rotate180 => [(1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (1.0, 0.0)],
+        Orientation::Rotate270 => [(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)],
+        Orientation::FlipHorizontal => [(1.0, 0.0), (0.0, 0.0), (0.0, 1.0), (1.0, 1.0)],
+        Orientation::FlipVertical => [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)],
+        Orientation::Rotate90FlipH => [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)],
+        Orientation::Rotate270FlipH => [(1.0, 1.0), (1.0, 0.0), (0.0, 0.0), (0.0, 1.0)],
+    };
+
+    uvs.map(|(u, v)| pos2(u, v))
+}
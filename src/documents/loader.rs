@@ -75,30 +75,46 @@ impl<T: Send + 'static, E: Send + 'static> DocumentContent<T, E> {
         ctx: &egui::Context,
         on_loaded_message: (MessageSource, AppMessage),
         sender: AppMessageSender,
-        load_fn: fn(path_buf: PathBuf, ctx: &egui::Context) -> Result<T, E>,
+        load_fn: impl FnOnce(PathBuf, &egui::Context) -> Result<T, E> + Send + 'static,
     ) -> Self {
-        let ctx = ctx.clone();
-        let handle = thread::Builder::new()
-            .name(format!("loader: {:?}", path))
-            .spawn(move || {
-                info!("Loading {}", path.display());
+        // threads are not available on the web, files are in memory there (see `files`), so load them immediately.
+        #[cfg(target_arch = "wasm32")]
+        {
+            info!("Loading {}", path.display());
+            let state = match load_fn(path, ctx) {
+                Ok(content) => LoaderState::Loaded(content),
+                Err(error) => LoaderState::Error(error),
+            };
+            sender.send(on_loaded_message).expect("sent");
 
-                // add a 2-second delay to simulate slow loading.
-                // this is done to that thread notification can be observed in the UI; a solution is required
-                // to have the UI update when loading is complete.
-                thread::sleep(Duration::from_secs(1));
+            return Self { state };
+        }
 
-                let content: Result<T, E> = load_fn(path, &ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let ctx = ctx.clone();
+            let handle = thread::Builder::new()
+                .name(format!("loader: {:?}", path))
+                .spawn(move || {
+                    info!("Loading {}", path.display());
 
-                // send a message via the sender to cause the UI to be updated when loading is complete.
-                sender.send(on_loaded_message).expect("sent");
+                    // add a 2-second delay to simulate slow loading.
+                    // this is done to that thread notification can be observed in the UI; a solution is required
+                    // to have the UI update when loading is complete.
+                    thread::sleep(Duration::from_secs(1));
 
-                content
-            })
-            .unwrap();
+                    let content: Result<T, E> = load_fn(path, &ctx);
 
-        Self {
-            state: LoaderState::Loading(Some(handle)),
+                    // send a message via the sender to cause the UI to be updated when loading is complete.
+                    sender.send(on_loaded_message).expect("sent");
+
+                    content
+                })
+                .unwrap();
+
+            Self {
+                state: LoaderState::Loading(Some(handle)),
+            }
         }
     }
 
@@ -0,0 +1,148 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::sidebar::sidebar_ui;
+use crate::documents::{DocumentContext, DocumentKey};
+use egui::{TextEdit, Ui};
+use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
+use egui_i18n::tr;
+use log::error;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum MarkdownLayout {
+    /// the editor and the preview, side-by-side.
+    #[default]
+    Split,
+    Editor,
+    Preview,
+}
+
+impl MarkdownLayout {
+    const ALL: [MarkdownLayout; 3] = [MarkdownLayout::Split, MarkdownLayout::Editor, MarkdownLayout::Preview];
+
+    fn label(&self) -> String {
+        match self {
+            MarkdownLayout::Split => tr!("markdown-layout-split"),
+            MarkdownLayout::Editor => tr!("markdown-layout-editor"),
+            MarkdownLayout::Preview => tr!("markdown-layout-preview"),
+        }
+    }
+}
+
+pub struct MarkdownDocument {
+    pub path: PathBuf,
+
+    loader: DocumentContent<String, MarkdownLoaderError>,
+    layout: MarkdownLayout,
+    /// caches the state of the preview, e.g. images and syntax highlighting.
+    cache: CommonMarkCache,
+}
+
+enum MarkdownLoaderError {
+    Error,
+}
+
+impl MarkdownDocument {
+    pub fn from_path(path: PathBuf, ctx: &egui::Context, document_key: DocumentKey, sender: AppMessageSender) -> Self {
+        let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let loader = DocumentContent::load(path.clone(), ctx, message, sender, |path, _ctx| {
+            files::read_to_string(&path).map_err(|cause| {
+                error!("Failed to load markdown. path: {:?}, cause: {}", path, cause);
+                MarkdownLoaderError::Error
+            })
+        });
+
+        Self {
+            path,
+            loader,
+            layout: MarkdownLayout::default(),
+            cache: CommonMarkCache::default(),
+        }
+    }
+
+    /// `None` until loaded.
+    pub fn text(&self) -> Option<&str> {
+        self.loader.content().map(String::as_str)
+    }
+
+    pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
+        self.loader.update();
+
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (
+                tr!("document-sidebar-text-length"),
+                self.loader
+                    .content()
+                    .map_or(tr!("generic-unknown-value"), |content| content.len().to_string()),
+            ),
+        ];
+
+        sidebar_ui(ui, rows, |ui| {
+            ui.heading(tr!("markdown-layout-heading"));
+            ui.horizontal(|ui| {
+                for layout in MarkdownLayout::ALL {
+                    ui.selectable_value(&mut self.layout, layout, layout.label());
+                }
+            });
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.content_ui(ui);
+        });
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        if self.loader.is_error() {
+            ui.label(tr!("file-loading-error"));
+            return;
+        }
+
+        // relative links and images are resolved against the directory containing the file.
+        // not a `Url`, egui's file loader uses everything after `file://` as the path, without percent-decoding it.
+        let base_uri = self
+            .path
+            .parent()
+            .and_then(|directory| std::path::absolute(directory).ok())
+            .map_or_else(|| "file://".to_string(), |directory| format!("file://{}/", directory.display()));
+
+        let Some(content) = self.loader.content_mut() else {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+            return;
+        };
+
+        let editor_ui = |ui: &mut Ui, content: &mut String| {
+            egui::ScrollArea::vertical()
+                .id_salt("editor")
+                .show(ui, |ui| {
+                    ui.add_sized(ui.available_size(), TextEdit::multiline(content).code_editor());
+                });
+        };
+
+        let cache = &mut self.cache;
+        let mut preview_ui = |ui: &mut Ui, content: &str| {
+            egui::ScrollArea::vertical()
+                .id_salt("preview")
+                .show(ui, |ui| {
+                    // unlike the rest of the document, the preview should wrap to the available width.
+                    ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                    CommonMarkViewer::new()
+                        .default_implicit_uri_scheme(base_uri.as_str())
+                        .show(ui, cache, content);
+                });
+        };
+
+        match self.layout {
+            MarkdownLayout::Split => {
+                ui.columns(2, |columns| {
+                    editor_ui(&mut columns[0], content);
+                    preview_ui(&mut columns[1], content);
+                });
+            }
+            MarkdownLayout::Editor => editor_ui(ui, content),
+            MarkdownLayout::Preview => preview_ui(ui, content),
+        }
+    }
+}
@@ -1,23 +1,96 @@
 use std::sync::{Arc, Mutex};
 use crate::app::{AppMessage, Config, MessageSource};
+use crate::documents::animation::AnimationDocument;
+use crate::documents::hex::HexDocument;
 use crate::documents::image::ImageDocument;
+use crate::documents::markdown::MarkdownDocument;
+use crate::documents::structured::StructuredDocument;
+use crate::documents::table::TableDocument;
 use crate::documents::text::TextDocument;
+use egui_i18n::tr;
 use egui_inbox::UiInboxSender;
+use serde::{Deserialize, Serialize};
 use slotmap::new_key_type;
 
+pub mod files;
 pub mod loader;
+pub mod sidebar;
 
+pub mod animation;
+pub mod comparison;
+pub mod hex;
 pub mod image;
+pub mod markdown;
+pub mod structured;
+pub mod table;
 pub mod text;
+pub mod text_diff;
 
 new_key_type! {
     /// A key for a document
     pub struct DocumentKey;
 }
 
+/// The document kinds a file can be opened as, regardless of its extension.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum OpenAs {
+    Text,
+    Markdown,
+    Json,
+    Toml,
+    Table,
+    /// animated images are opened as animations.
+    Image,
+    Hex,
+}
+
+impl OpenAs {
+    pub const ALL: [OpenAs; 7] = [
+        OpenAs::Text,
+        OpenAs::Markdown,
+        OpenAs::Json,
+        OpenAs::Toml,
+        OpenAs::Table,
+        OpenAs::Image,
+        OpenAs::Hex,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            OpenAs::Text => tr!("open-as-text"),
+            OpenAs::Markdown => tr!("open-as-markdown"),
+            OpenAs::Json => tr!("open-as-json"),
+            OpenAs::Toml => tr!("open-as-toml"),
+            OpenAs::Table => tr!("open-as-table"),
+            OpenAs::Image => tr!("open-as-image"),
+            OpenAs::Hex => tr!("open-as-hex"),
+        }
+    }
+}
+
 pub enum DocumentKind {
     TextDocument(TextDocument),
     ImageDocument(ImageDocument),
+    AnimationDocument(AnimationDocument),
+    MarkdownDocument(MarkdownDocument),
+    StructuredDocument(StructuredDocument),
+    TableDocument(TableDocument),
+    HexDocument(HexDocument),
+}
+
+impl DocumentKind {
+    /// `true` if there are unsaved changes, which would be lost if the document was reloaded.
+    pub fn is_modified(&self) -> bool {
+        match self {
+            DocumentKind::ImageDocument(document) => document.is_modified(),
+            DocumentKind::StructuredDocument(document) => document.is_modified(),
+            DocumentKind::TableDocument(document) => document.is_modified(),
+            DocumentKind::TextDocument(_)
+            | DocumentKind::AnimationDocument(_)
+            | DocumentKind::MarkdownDocument(_)
+            | DocumentKind::HexDocument(_) => false,
+        }
+    }
 }
 
 pub struct DocumentContext {
@@ -0,0 +1,84 @@
+use egui::{frame, Margin, Ui};
+use egui_taffy::taffy::prelude::{auto, fit_content, fr, length, percent};
+use egui_taffy::taffy::{AlignItems, Display, FlexDirection, Size, Style};
+use egui_taffy::{tui, TuiBuilderLogic};
+
+/// Shows the resizable sidebar on the left of a document, call before showing the content in a `CentralPanel`.
+///
+/// `rows` are shown in a grid of labels and values, e.g. the file path, followed by `extra`, e.g. the settings or
+/// actions of the document.
+pub fn sidebar_ui(ui: &mut Ui, rows: Vec<(String, String)>, extra: impl FnOnce(&mut Ui)) {
+    ui.ctx().style_mut(|style| {
+        // if this is not done, text in labels/checkboxes/etc wraps
+        style.wrap_mode = Some(egui::TextWrapMode::Extend);
+        style.spacing.window_margin = Margin::same(0);
+    });
+
+    let default_style = || Style {
+        padding: length(2.),
+        gap: length(2.),
+        ..Default::default()
+    };
+
+    let mut frame = frame::Frame::new();
+    frame.outer_margin = Margin::same(0);
+    frame.inner_margin = Margin::same(0);
+
+    egui::SidePanel::left(ui.id().with("sidebar"))
+        .resizable(true)
+        .frame(frame)
+        .show_inside(ui, |ui| {
+            egui::ScrollArea::both().show(ui, |ui| {
+                tui(ui, ui.id().with("grid"))
+                    .reserve_available_width()
+                    .style(Style {
+                        align_items: Some(AlignItems::Stretch),
+                        flex_direction: FlexDirection::Column,
+                        size: Size {
+                            width: percent(1.),
+                            height: auto(),
+                        },
+                        padding: length(0.),
+                        gap: length(0.),
+                        ..default_style()
+                    })
+                    .show(|tui| {
+                        tui.style(Style {
+                            flex_grow: 1.0,
+                            display: Display::Grid,
+                            grid_template_columns: vec![fit_content(percent(1.)), fr(1.)],
+                            grid_template_rows: vec![fr(1.), fr(1.)],
+
+                            // ensure items are centered vertically on rows
+                            align_items: Some(AlignItems::Center),
+                            padding: length(0.),
+                            margin: length(0.),
+                            ..default_style()
+                        })
+                        .add(|tui| {
+                            for (label, value) in rows {
+                                tui.style(Style { ..default_style() })
+                                    .add_with_border(|tui| {
+                                        tui.label(label.as_str());
+                                    });
+                                tui.style(Style {
+                                    flex_grow: 1.0,
+                                    ..default_style()
+                                })
+                                .add_with_border(|tui| {
+                                    tui.label(value.as_str());
+                                });
+                            }
+                            // end of grid content
+                        });
+
+                        tui.ui(|ui| {
+                            extra(ui);
+                        });
+                        // end of container content
+                    });
+                // end of scroll content
+            });
+            // end of sidebar content
+        });
+}
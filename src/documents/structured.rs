@@ -0,0 +1,323 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::structured::node::{path_to_string, Node, ParseError, PathSegment, StructuredFormat};
+use crate::documents::sidebar::sidebar_ui;
+use crate::documents::{DocumentContext, DocumentKey};
+use egui::collapsing_header::CollapsingState;
+use egui::{RichText, TextEdit, Ui};
+use egui_i18n::tr;
+use log::{error, info};
+use std::path::PathBuf;
+
+pub mod node;
+
+/// the maximum number of characters of a value shown in the tree, the full value is shown for the selected node.
+const MAX_SUMMARY_LENGTH: usize = 80;
+
+/// A JSON or TOML file, shown as text and as a tree.
+pub struct StructuredDocument {
+    pub path: PathBuf,
+
+    format: StructuredFormat,
+    loader: DocumentContent<StructuredContent, StructuredLoaderError>,
+    /// the path of the selected node, the root is selected when empty.
+    selection: Option<Vec<PathSegment>>,
+    /// there are edits that have not been saved.
+    modified: bool,
+    save_error: Option<String>,
+}
+
+struct StructuredContent {
+    text: String,
+    /// updated whenever the text is changed.
+    parsed: Result<Node, ParseError>,
+}
+
+enum StructuredLoaderError {
+    Error,
+}
+
+impl StructuredDocument {
+    pub fn from_path(
+        path: PathBuf,
+        format: StructuredFormat,
+        ctx: &egui::Context,
+        document_key: DocumentKey,
+        sender: AppMessageSender,
+    ) -> Self {
+        let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let loader = DocumentContent::load(path.clone(), ctx, message, sender, move |path, _ctx| {
+            let text = files::read_to_string(&path).map_err(|cause| {
+                error!("Failed to load file. path: {:?}, cause: {}", path, cause);
+                StructuredLoaderError::Error
+            })?;
+
+            // parsing large files takes a while, so the initial parse is done here, not in the ui.
+            let parsed = format.parse(&text);
+
+            Ok(StructuredContent { text, parsed })
+        });
+
+        Self {
+            path,
+            format,
+            loader,
+            selection: None,
+            modified: false,
+            save_error: None,
+        }
+    }
+
+    /// `None` until loaded.
+    pub fn text(&self) -> Option<&str> {
+        self.loader.content().map(|content| content.text.as_str())
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
+        self.loader.update();
+
+        let content = self.loader.content();
+        let unknown = || tr!("generic-unknown-value");
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (tr!("structured-sidebar-format"), self.format.name().to_string()),
+            (
+                tr!("document-sidebar-text-length"),
+                content.map_or_else(unknown, |content| content.text.len().to_string()),
+            ),
+            (
+                tr!("structured-sidebar-status"),
+                content.map_or_else(unknown, |content| match &content.parsed {
+                    Ok(_) => tr!("structured-status-valid"),
+                    Err(_) => tr!("structured-status-invalid"),
+                }),
+            ),
+        ];
+
+        sidebar_ui(ui, rows, |ui| {
+            if let Some(content) = self.loader.content_mut() {
+                if Self::actions_ui(ui, self.format, content) {
+                    self.modified = true;
+                }
+                self.save_ui(ui);
+            }
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.content_ui(ui);
+        });
+    }
+
+    /// returns `true` if the text was changed.
+    fn actions_ui(ui: &mut Ui, format: StructuredFormat, content: &mut StructuredContent) -> bool {
+        ui.heading(tr!("structured-actions-heading"));
+
+        let mut changed = false;
+        let valid = content.parsed.is_ok();
+        ui.horizontal(|ui| {
+            let mut pretty_print_button = ui.add_enabled(
+                valid && format.can_pretty_print(),
+                egui::Button::new(tr!("structured-action-pretty-print")),
+            );
+            if !format.can_pretty_print() {
+                pretty_print_button =
+                    pretty_print_button.on_disabled_hover_text(tr!("structured-action-pretty-print-unsupported"));
+            }
+            if pretty_print_button.clicked() {
+                if let Some(text) = format.pretty_print(&content.text) {
+                    content.text = text;
+                    content.parsed = format.parse(&content.text);
+                    changed = true;
+                }
+            }
+
+            let mut minify_button = ui.add_enabled(
+                valid && format.can_minify(),
+                egui::Button::new(tr!("structured-action-minify")),
+            );
+            if !format.can_minify() {
+                minify_button = minify_button.on_disabled_hover_text(tr!("structured-action-minify-unsupported"));
+            }
+            if minify_button.clicked() {
+                if let Some(text) = format.minify(&content.text) {
+                    content.text = text;
+                    content.parsed = format.parse(&content.text);
+                    changed = true;
+                }
+            }
+        });
+
+        changed
+    }
+
+    fn save_ui(&mut self, ui: &mut Ui) {
+        ui.heading(tr!("structured-save-heading"));
+
+        if ui
+            .add_enabled(self.modified, egui::Button::new(tr!("structured-save-button")))
+            .clicked()
+        {
+            self.save();
+        }
+
+        if self.modified {
+            ui.label(tr!("structured-save-modified"));
+        }
+        if let Some(cause) = &self.save_error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("{} {}", tr!("structured-save-failed"), cause));
+        }
+    }
+
+    /// the text is saved as-is, even if it has errors, so work in progress is not lost.
+    fn save(&mut self) {
+        let Some(content) = self.loader.content() else {
+            return;
+        };
+
+        match std::fs::write(&self.path, &content.text) {
+            Ok(()) => {
+                info!("saved structured document. path: {:?}", self.path);
+                self.modified = false;
+                self.save_error = None;
+            }
+            Err(cause) => {
+                error!("Failed to save structured document. path: {:?}, cause: {}", self.path, cause);
+                self.save_error = Some(cause.to_string());
+            }
+        }
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        if self.loader.is_error() {
+            ui.label(tr!("file-loading-error"));
+            return;
+        }
+
+        let Some(content) = self.loader.content_mut() else {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+            return;
+        };
+
+        ui.columns(2, |columns| {
+            let ui = &mut columns[0];
+            if let Err(cause) = &content.parsed {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!(
+                        "{} {}, {} {}: {}",
+                        tr!("structured-error-line"),
+                        cause.line,
+                        tr!("structured-error-column"),
+                        cause.column,
+                        cause.message
+                    ),
+                );
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("text")
+                .show(ui, |ui| {
+                    let response = ui.add_sized(ui.available_size(), TextEdit::multiline(&mut content.text).code_editor());
+                    if response.changed() {
+                        content.parsed = self.format.parse(&content.text);
+                        self.modified = true;
+                    }
+                });
+
+            let ui = &mut columns[1];
+            match &content.parsed {
+                Ok(root) => Self::tree_ui(ui, root, &mut self.selection),
+                Err(_) => {
+                    ui.label(tr!("structured-tree-unavailable"));
+                }
+            }
+        });
+    }
+
+    fn tree_ui(ui: &mut Ui, root: &Node, selection: &mut Option<Vec<PathSegment>>) {
+        // the selected node may no longer exist after editing.
+        let selected_node = selection.as_ref().and_then(|path| root.get(path));
+        if selected_node.is_none() {
+            *selection = None;
+        }
+
+        if let (Some(path), Some(node)) = (selection.clone(), selected_node) {
+            Self::breadcrumb_ui(ui, &path, selection);
+
+            let value = match node {
+                Node::String(value) => value.clone(),
+                _ => node.summary(),
+            };
+            ui.horizontal(|ui| {
+                ui.label(tr!("structured-selected-value"));
+                ui.monospace(value);
+            });
+            ui.separator();
+        }
+
+        egui::ScrollArea::both()
+            .id_salt("tree")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                let mut path = Vec::new();
+                Self::node_ui(ui, "$".to_string(), root, &mut path, selection);
+            });
+    }
+
+    fn breadcrumb_ui(ui: &mut Ui, path: &[PathSegment], selection: &mut Option<Vec<PathSegment>>) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.small_button("$").clicked() {
+                *selection = Some(Vec::new());
+            }
+            for (index, segment) in path.iter().enumerate() {
+                ui.label("›");
+                if ui.small_button(segment.to_string()).clicked() {
+                    *selection = Some(path[..=index].to_vec());
+                }
+            }
+            ui.separator();
+            if ui.small_button(tr!("structured-copy-path")).clicked() {
+                ui.ctx().copy_text(path_to_string(path));
+            }
+        });
+    }
+
+    fn node_ui(ui: &mut Ui, label: String, node: &Node, path: &mut Vec<PathSegment>, selection: &mut Option<Vec<PathSegment>>) {
+        let selected = selection.as_deref() == Some(path.as_slice());
+
+        let Some(children) = node.children() else {
+            let mut summary = node.summary();
+            if summary.chars().count() > MAX_SUMMARY_LENGTH {
+                summary = summary.chars().take(MAX_SUMMARY_LENGTH).collect::<String>() + "…";
+            }
+            let text = RichText::new(format!("{}: {}", label, summary)).monospace();
+            if ui.selectable_label(selected, text).clicked() {
+                *selection = Some(path.clone());
+            }
+            return;
+        };
+
+        let id = ui.make_persistent_id(("structured-node", &*path));
+        // only the root is expanded initially, large files can have a huge number of nodes.
+        CollapsingState::load_with_default_open(ui.ctx(), id, path.is_empty())
+            .show_header(ui, |ui| {
+                let text = RichText::new(format!("{} {}", label, node.summary())).monospace();
+                if ui.selectable_label(selected, text).clicked() {
+                    *selection = Some(path.clone());
+                }
+            })
+            .body(|ui| {
+                for (segment, child) in children {
+                    let label = segment.to_string();
+                    path.push(segment);
+                    Self::node_ui(ui, label, child, path, selection);
+                    path.pop();
+                }
+            });
+    }
+}
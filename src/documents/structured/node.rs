@@ -0,0 +1,243 @@
+use std::fmt::{Display, Formatter};
+
+/// The formats are converted to a common tree, so the tree view does not need to know about the format.
+#[derive(Clone, Debug)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    /// JSON numbers are kept as text, so they are shown exactly as written, e.g. large integers and exponents, this
+    /// relies on serde_json's `arbitrary_precision` feature.  TOML numbers are shown as parsed, i.e. `i64` or `f64`.
+    Number(String),
+    String(String),
+    /// TOML only
+    DateTime(String),
+    Array(Vec<Node>),
+    /// in the order they appear in the file.
+    Object(Vec<(String, Node)>),
+}
+
+impl Node {
+    pub fn children(&self) -> Option<Vec<(PathSegment, &Node)>> {
+        match self {
+            Node::Array(items) => Some(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, node)| (PathSegment::Index(index), node))
+                    .collect(),
+            ),
+            Node::Object(entries) => Some(
+                entries
+                    .iter()
+                    .map(|(key, node)| (PathSegment::Key(key.clone()), node))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// `None` if there is no node at the path, e.g. the text was edited and the node was removed.
+    pub fn get(&self, path: &[PathSegment]) -> Option<&Node> {
+        let Some((first, remainder)) = path.split_first() else {
+            return Some(self);
+        };
+
+        let child = match (self, first) {
+            (Node::Array(items), PathSegment::Index(index)) => items.get(*index),
+            (Node::Object(entries), PathSegment::Key(key)) => entries
+                .iter()
+                .find(|(candidate, _)| candidate == key)
+                .map(|(_, node)| node),
+            _ => None,
+        };
+
+        child.and_then(|child| child.get(remainder))
+    }
+
+    /// a short description of the node, for containers this is the number of children.
+    pub fn summary(&self) -> String {
+        match self {
+            Node::Null => "null".to_string(),
+            Node::Bool(value) => value.to_string(),
+            Node::Number(value) | Node::DateTime(value) => value.clone(),
+            Node::String(value) => format!("{:?}", value),
+            Node::Array(items) => format!("[{}]", items.len()),
+            Node::Object(entries) => format!("{{{}}}", entries.len()),
+        }
+    }
+}
+
+impl From<serde_json::Value> for Node {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Node::Null,
+            serde_json::Value::Bool(value) => Node::Bool(value),
+            serde_json::Value::Number(value) => Node::Number(value.to_string()),
+            serde_json::Value::String(value) => Node::String(value),
+            serde_json::Value::Array(items) => Node::Array(items.into_iter().map(Node::from).collect()),
+            serde_json::Value::Object(entries) => Node::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Node::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<toml::Value> for Node {
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::String(value) => Node::String(value),
+            toml::Value::Integer(value) => Node::Number(value.to_string()),
+            toml::Value::Float(value) => Node::Number(value.to_string()),
+            toml::Value::Boolean(value) => Node::Bool(value),
+            toml::Value::Datetime(value) => Node::DateTime(value.to_string()),
+            toml::Value::Array(items) => Node::Array(items.into_iter().map(Node::from).collect()),
+            toml::Value::Table(entries) => Node::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Node::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Key(key) => write!(f, "{}", key),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+/// e.g. `$.dependencies["egui-i18n"].features[0]`
+pub fn path_to_string(path: &[PathSegment]) -> String {
+    let mut result = "$".to_string();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) if is_identifier(key) => {
+                result.push('.');
+                result.push_str(key);
+            }
+            PathSegment::Key(key) => result.push_str(&format!("[{:?}]", key)),
+            PathSegment::Index(index) => result.push_str(&format!("[{}]", index)),
+        }
+    }
+    result
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|char| char.is_alphanumeric() || char == '_')
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StructuredFormat {
+    Json,
+    Toml,
+}
+
+impl StructuredFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "json" => Some(StructuredFormat::Json),
+            "toml" => Some(StructuredFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StructuredFormat::Json => "JSON",
+            StructuredFormat::Toml => "TOML",
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Node, ParseError> {
+        match self {
+            StructuredFormat::Json => serde_json::from_str::<serde_json::Value>(text)
+                .map(Node::from)
+                .map_err(|cause| ParseError {
+                    message: cause.to_string(),
+                    line: cause.line(),
+                    column: cause.column(),
+                }),
+            StructuredFormat::Toml => toml::from_str::<toml::Value>(text)
+                .map(Node::from)
+                .map_err(|cause| {
+                    let offset = cause.span().map_or(0, |span| span.start);
+                    let (line, column) = line_and_column(text, offset);
+                    ParseError {
+                        message: cause.message().to_string(),
+                        line,
+                        column,
+                    }
+                }),
+        }
+    }
+
+    /// re-formats the text, `None` if it could not be parsed, or if the format cannot be pretty printed, i.e. TOML,
+    /// as re-formatting it would lose the comments and the layout.
+    pub fn pretty_print(&self, text: &str) -> Option<String> {
+        match self {
+            StructuredFormat::Json => serde_json::from_str::<serde_json::Value>(text)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok()),
+            StructuredFormat::Toml => None,
+        }
+    }
+
+    pub fn can_pretty_print(&self) -> bool {
+        matches!(self, StructuredFormat::Json)
+    }
+
+    /// `None` if it could not be parsed, or if the format cannot be minified, i.e. TOML, which is line-based.
+    pub fn minify(&self, text: &str) -> Option<String> {
+        match self {
+            StructuredFormat::Json => serde_json::from_str::<serde_json::Value>(text)
+                .ok()
+                .and_then(|value| serde_json::to_string(&value).ok()),
+            StructuredFormat::Toml => None,
+        }
+    }
+
+    pub fn can_minify(&self) -> bool {
+        matches!(self, StructuredFormat::Json)
+    }
+}
+
+/// The location of a parse error, line and column are 1-based.
+#[derive(Clone, Debug)]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} {}", self.line, self.column, self.message)
+    }
+}
+
+/// 1-based, columns are counted in characters, not bytes.
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
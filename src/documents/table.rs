@@ -0,0 +1,544 @@
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::files;
+use crate::documents::loader::DocumentContent;
+use crate::documents::sidebar::sidebar_ui;
+use crate::documents::{DocumentContext, DocumentKey};
+use egui::{Key, Label, Sense, TextEdit, Ui};
+use egui_extras::{Column, TableBuilder};
+use egui_i18n::tr;
+use log::{error, info};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// the number of records used to detect the delimiter.
+const DETECTION_RECORDS: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Delimiter {
+    Comma,
+    Semicolon,
+    Tab,
+    Pipe,
+}
+
+impl Delimiter {
+    const ALL: [Delimiter; 4] = [Delimiter::Comma, Delimiter::Semicolon, Delimiter::Tab, Delimiter::Pipe];
+
+    fn byte(&self) -> u8 {
+        match self {
+            Delimiter::Comma => b',',
+            Delimiter::Semicolon => b';',
+            Delimiter::Tab => b'\t',
+            Delimiter::Pipe => b'|',
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Delimiter::Comma => tr!("table-delimiter-comma"),
+            Delimiter::Semicolon => tr!("table-delimiter-semicolon"),
+            Delimiter::Tab => tr!("table-delimiter-tab"),
+            Delimiter::Pipe => tr!("table-delimiter-pipe"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TableSettings {
+    delimiter: Delimiter,
+    /// the first row contains the column names.
+    has_header: bool,
+}
+
+impl TableSettings {
+    /// `.tsv` files always use tabs, otherwise the delimiter that occurs most consistently in the first records is
+    /// used.
+    fn detect(path: &Path, text: &str) -> Self {
+        let is_tsv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tsv"));
+        let delimiter = if is_tsv {
+            Delimiter::Tab
+        } else {
+            // `max_by_key` returns the last of equal elements, reversed so ties go to the first, e.g. a single column
+            // of values uses commas.
+            Delimiter::ALL
+                .into_iter()
+                .rev()
+                .max_by_key(|delimiter| delimiter_count(text, *delimiter))
+                .unwrap()
+        };
+
+        let has_header = parse_rows(text, delimiter)
+            .ok()
+            .and_then(|rows| rows.into_iter().next())
+            .is_some_and(|first| {
+                // column names are usually non-empty and not numbers.
+                first
+                    .iter()
+                    .all(|cell| !cell.trim().is_empty() && cell.trim().parse::<f64>().is_err())
+            });
+
+        Self { delimiter, has_header }
+    }
+}
+
+/// the minimum number of delimiters across the first records, so a delimiter that only appears in some records, e.g.
+/// in a free-text column, is not preferred.
+///
+/// the records are parsed, so delimiters in quoted fields are not counted.
+fn delimiter_count(text: &str, delimiter: Delimiter) -> usize {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter.byte())
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .records()
+        .take(DETECTION_RECORDS)
+        .map(|record| record.map_or(0, |record| record.len().saturating_sub(1)))
+        .min()
+        .unwrap_or(0)
+}
+
+struct Table {
+    columns: Vec<String>,
+    /// all rows have the same number of cells as there are columns.
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn parse(text: &str, settings: &TableSettings) -> Result<Self, csv::Error> {
+        let mut rows = parse_rows(text, settings.delimiter)?;
+
+        let mut columns = match settings.has_header && !rows.is_empty() {
+            true => rows.remove(0),
+            false => Vec::new(),
+        };
+
+        // rows may have different lengths, the missing cells are added, so every row can be edited.
+        let column_count = rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(columns.len()))
+            .max()
+            .unwrap_or(0);
+        for index in columns.len()..column_count {
+            columns.push((index + 1).to_string());
+        }
+        for row in rows.iter_mut() {
+            row.resize(column_count, String::new());
+        }
+
+        Ok(Self { columns, rows })
+    }
+
+    fn to_text(&self, settings: &TableSettings) -> Result<String, csv::Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(settings.delimiter.byte())
+            .from_writer(Vec::new());
+
+        if settings.has_header {
+            writer.write_record(&self.columns)?;
+        }
+        for row in self.rows.iter() {
+            writer.write_record(row)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|cause| csv::Error::from(cause.into_error()))?;
+
+        // the input was valid UTF-8, so the output is too.
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// the indexes of the rows, in display order.
+    fn sorted_order(&self, sort: Option<Sort>) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+
+        if let Some(Sort { column, ascending }) = sort {
+            order.sort_by(|a, b| {
+                let ordering = compare_cells(&self.rows[*a][column], &self.rows[*b][column]);
+                match ascending {
+                    true => ordering,
+                    false => ordering.reverse(),
+                }
+            });
+        }
+
+        order
+    }
+}
+
+fn parse_rows(text: &str, delimiter: Delimiter) -> Result<Vec<Vec<String>>, csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter.byte())
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    reader
+        .records()
+        .map(|record| record.map(|record| record.iter().map(str::to_string).collect()))
+        .collect()
+}
+
+/// numbers are compared numerically, everything else as text.
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Sort {
+    column: usize,
+    ascending: bool,
+}
+
+struct TableContent {
+    table: Table,
+    /// detected when loading, can be changed in the sidebar.
+    settings: TableSettings,
+    /// the text as loaded or last saved, parsed again when the settings are changed.
+    text: String,
+}
+
+struct CellEdit {
+    row: usize,
+    column: usize,
+    value: String,
+    /// the text edit is focused on the first frame only.
+    focus: bool,
+}
+
+#[derive(Debug)]
+enum TableLoaderError {
+    Io(std::io::Error),
+    Csv(csv::Error),
+}
+
+/// A CSV or TSV file.
+pub struct TableDocument {
+    pub path: PathBuf,
+
+    loader: DocumentContent<TableContent, TableLoaderError>,
+    sort: Option<Sort>,
+    /// the display order of the rows, `None` when it needs to be re-computed, e.g. after sorting or editing.
+    order: Option<Vec<usize>>,
+    editing: Option<CellEdit>,
+    /// there are edits that have not been saved.
+    modified: bool,
+    save_error: Option<String>,
+}
+
+impl TableDocument {
+    pub fn from_path(path: PathBuf, ctx: &egui::Context, document_key: DocumentKey, sender: AppMessageSender) -> Self {
+        let message = (MessageSource::Document(document_key), AppMessage::Refresh);
+        let loader = DocumentContent::load(path.clone(), ctx, message, sender, |path, _ctx| {
+            let text = files::read_to_string(&path).map_err(TableLoaderError::Io)?;
+
+            let settings = TableSettings::detect(&path, &text);
+            info!("loading table. path: {:?}, settings: {:?}", path, settings);
+
+            let table = Table::parse(&text, &settings).map_err(TableLoaderError::Csv)?;
+
+            Ok(TableContent { table, settings, text })
+        });
+
+        Self {
+            path,
+            loader,
+            sort: None,
+            order: None,
+            editing: None,
+            modified: false,
+            save_error: None,
+        }
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
+        self.loader.update();
+
+        let content = self.loader.content();
+        let unknown = || tr!("generic-unknown-value");
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (
+                tr!("table-sidebar-rows"),
+                content.map_or_else(unknown, |content| content.table.rows.len().to_string()),
+            ),
+            (
+                tr!("table-sidebar-columns"),
+                content.map_or_else(unknown, |content| content.table.columns.len().to_string()),
+            ),
+        ];
+
+        sidebar_ui(ui, rows, |ui| {
+            if self.loader.content().is_some() {
+                self.settings_ui(ui);
+                self.save_ui(ui);
+            }
+        });
+
+        egui::CentralPanel::default().show_inside(ui, |ui| {
+            self.content_ui(ui);
+        });
+    }
+
+    fn settings_ui(&mut self, ui: &mut Ui) {
+        let Some(content) = self.loader.content_mut() else {
+            return;
+        };
+
+        ui.heading(tr!("table-settings-heading"));
+
+        // the text is parsed again using the new settings, which would discard the edits.
+        let mut settings = content.settings;
+        ui.add_enabled_ui(!self.modified, |ui| {
+            egui::ComboBox::from_label(tr!("table-settings-delimiter"))
+                .selected_text(settings.delimiter.label())
+                .show_ui(ui, |ui| {
+                    for delimiter in Delimiter::ALL {
+                        ui.selectable_value(&mut settings.delimiter, delimiter, delimiter.label());
+                    }
+                });
+            ui.checkbox(&mut settings.has_header, tr!("table-settings-header"));
+        })
+        .response
+        .on_disabled_hover_text(tr!("table-settings-modified"));
+
+        if settings != content.settings {
+            match Table::parse(&content.text, &settings) {
+                Ok(table) => {
+                    content.table = table;
+                    content.settings = settings;
+                    self.sort = None;
+                    self.order = None;
+                    self.editing = None;
+                }
+                Err(cause) => {
+                    error!("Failed to apply table settings. path: {:?}, cause: {}", self.path, cause);
+                }
+            }
+        }
+    }
+
+    fn save_ui(&mut self, ui: &mut Ui) {
+        ui.heading(tr!("table-save-heading"));
+
+        if ui
+            .add_enabled(self.modified, egui::Button::new(tr!("table-save-button")))
+            .clicked()
+        {
+            self.save();
+        }
+
+        if self.modified {
+            ui.label(tr!("table-save-modified"));
+        }
+        if let Some(cause) = &self.save_error {
+            ui.colored_label(ui.visuals().error_fg_color, format!("{} {}", tr!("table-save-failed"), cause));
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(content) = self.loader.content_mut() else {
+            return;
+        };
+
+        let result = content
+            .table
+            .to_text(&content.settings)
+            .map_err(|cause| cause.to_string())
+            .and_then(|text| {
+                std::fs::write(&self.path, &text)
+                    .map(|()| text)
+                    .map_err(|cause| cause.to_string())
+            });
+
+        match result {
+            Ok(text) => {
+                info!("saved table. path: {:?}", self.path);
+                content.text = text;
+                self.modified = false;
+                self.save_error = None;
+            }
+            Err(cause) => {
+                error!("Failed to save table. path: {:?}, cause: {}", self.path, cause);
+                self.save_error = Some(cause);
+            }
+        }
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        match self.loader.error() {
+            Some(TableLoaderError::Io(cause)) => {
+                ui.label(tr!("file-loading-error"));
+                ui.label(cause.to_string());
+                return;
+            }
+            Some(TableLoaderError::Csv(cause)) => {
+                ui.label(tr!("file-loading-error"));
+                ui.label(cause.to_string());
+                return;
+            }
+            None => {}
+        }
+
+        let Some(content) = self.loader.content_mut() else {
+            ui.spinner();
+            ui.label(tr!("file-loading"));
+            return;
+        };
+        let table = &mut content.table;
+
+        let order = self.order.get_or_insert_with(|| table.sorted_order(self.sort));
+
+        let row_height = ui.spacing().interact_size.y;
+        let mut clicked_column = None;
+        let mut start_editing = None;
+        let mut finish_editing = false;
+        let mut cancel_editing = false;
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .auto_shrink([false, false])
+                // the row number
+                .column(Column::auto())
+                .columns(
+                    Column::initial(120.0).at_least(40.0).clip(true),
+                    table.columns.len(),
+                )
+                .header(row_height, |mut header| {
+                    header.col(|ui| {
+                        ui.weak("#");
+                    });
+                    for (index, name) in table.columns.iter().enumerate() {
+                        header.col(|ui| {
+                            let indicator = match self.sort {
+                                Some(Sort { column, ascending: true }) if column == index => " ⏶",
+                                Some(Sort { column, ascending: false }) if column == index => " ⏷",
+                                _ => "",
+                            };
+                            let button = egui::Button::new(egui::RichText::new(format!("{}{}", name, indicator)).strong())
+                                .frame(false);
+                            if ui.add(button).on_hover_text(tr!("table-sort-hint")).clicked() {
+                                clicked_column = Some(index);
+                            }
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(row_height, order.len(), |mut row| {
+                        let row_index = order[row.index()];
+
+                        row.col(|ui| {
+                            ui.weak((row_index + 1).to_string());
+                        });
+
+                        for (column_index, cell) in table.rows[row_index].iter().enumerate() {
+                            row.col(|ui| match &mut self.editing {
+                                Some(edit) if edit.row == row_index && edit.column == column_index => {
+                                    let response = ui.add(TextEdit::singleline(&mut edit.value).desired_width(f32::INFINITY));
+                                    if edit.focus {
+                                        response.request_focus();
+                                        edit.focus = false;
+                                    }
+                                    if ui.input(|input| input.key_pressed(Key::Escape)) {
+                                        cancel_editing = true;
+                                    } else if response.lost_focus() {
+                                        finish_editing = true;
+                                    }
+                                }
+                                _ => {
+                                    let response = ui
+                                        .add(Label::new(cell.as_str()).truncate().sense(Sense::click()))
+                                        .on_hover_text(tr!("table-edit-hint"));
+                                    if response.double_clicked() {
+                                        start_editing = Some((row_index, column_index, cell.clone()));
+                                    }
+                                }
+                            });
+                        }
+                    });
+                });
+        });
+
+        if let Some(column) = clicked_column {
+            // ascending, then descending, then unsorted
+            self.sort = match self.sort {
+                Some(Sort { column: current, ascending: true }) if current == column => Some(Sort { column, ascending: false }),
+                Some(Sort { column: current, ascending: false }) if current == column => None,
+                _ => Some(Sort { column, ascending: true }),
+            };
+            self.order = None;
+        }
+
+        if cancel_editing {
+            self.editing = None;
+        } else if finish_editing {
+            if let Some(edit) = self.editing.take() {
+                let cell = &mut table.rows[edit.row][edit.column];
+                if *cell != edit.value {
+                    *cell = edit.value;
+                    self.modified = true;
+                    // the row may have moved
+                    self.order = None;
+                }
+            }
+        }
+
+        if let Some((row, column, value)) = start_editing {
+            self.editing = Some(CellEdit {
+                row,
+                column,
+                value,
+                focus: true,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_ignores_delimiters_in_quoted_fields() {
+        let text = "name;comment\n\"Smith, J\";\"a, b, c\"\nDoe;plain\n";
+        let settings = TableSettings::detect(Path::new("people.csv"), text);
+        assert_eq!(settings.delimiter, Delimiter::Semicolon);
+        assert!(settings.has_header);
+    }
+
+    #[test]
+    fn detect_prefers_the_most_consistent_delimiter() {
+        let text = "1,2,3\n4,5,6\n7,8;9\n";
+        let settings = TableSettings::detect(Path::new("numbers.csv"), text);
+        assert_eq!(settings.delimiter, Delimiter::Comma);
+        assert!(!settings.has_header);
+    }
+
+    #[test]
+    fn detect_uses_commas_for_a_single_column() {
+        let settings = TableSettings::detect(Path::new("names.csv"), "name\nSmith\nDoe\n");
+        assert_eq!(settings.delimiter, Delimiter::Comma);
+    }
+
+    #[test]
+    fn detect_uses_tabs_for_tsv_files() {
+        let settings = TableSettings::detect(Path::new("data.tsv"), "a,b\n");
+        assert_eq!(settings.delimiter, Delimiter::Tab);
+
+        let settings = TableSettings::detect(Path::new("DATA.TSV"), "a,b\n");
+        assert_eq!(settings.delimiter, Delimiter::Tab);
+    }
+}
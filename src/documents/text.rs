@@ -1,17 +1,19 @@
 use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::documents::sidebar::sidebar_ui;
 use crate::documents::{DocumentContext, DocumentKey};
-use egui::{frame, Margin, TextEdit, Ui};
+use egui::text::{CCursor, CCursorRange};
+use egui::{Align, Layout, TextEdit, Ui};
 use egui_i18n::tr;
-use egui_taffy::taffy::prelude::{auto, fit_content, fr, length, percent};
-use egui_taffy::taffy::{AlignItems, Display, FlexDirection, Size, Style};
-use egui_taffy::{tui, TuiBuilderLogic};
 use std::path::PathBuf;
+use crate::documents::files;
 use crate::documents::loader::DocumentContent;
 
 pub struct TextDocument {
     pub path: PathBuf,
 
     loader: DocumentContent<String, TextLoaderError>,
+    /// 1-based, the line to move the cursor to once the text has been loaded.
+    go_to_line: Option<usize>,
 }
 
 enum TextLoaderError {
@@ -23,108 +25,50 @@ impl TextDocument {
         Self {
             path,
             loader: DocumentContent::new("example content".to_string()),
+            go_to_line: None,
         }
     }
 
     pub fn from_path(path: PathBuf, ctx: &egui::Context, document_key: DocumentKey, sender: AppMessageSender) -> Self {
         let message = (MessageSource::Document(document_key), AppMessage::Refresh);
         let loader = DocumentContent::load(path.clone(), ctx, message, sender, |path, _ctx| {
-            match std::fs::read_to_string(path) {
+            match files::read_to_string(&path) {
                 Ok(content) => Ok(content),
                 Err(_cause) => Err(TextLoaderError::Error),
             }
         });
 
-        Self { path, loader }
+        Self {
+            path,
+            loader,
+            go_to_line: None,
+        }
+    }
+
+    /// 1-based, lines after the end of the text move the cursor to the end.
+    pub fn go_to_line(&mut self, line: usize) {
+        self.go_to_line = Some(line);
+    }
+
+    /// `None` until loaded.
+    pub fn text(&self) -> Option<&str> {
+        self.loader.content().map(String::as_str)
     }
 
     pub fn ui<'a>(&mut self, ui: &mut Ui, _context: &mut DocumentContext) {
         self.loader.update();
 
-        ui.ctx().style_mut(|style| {
-            // if this is not done, text in labels/checkboxes/etc wraps
-            style.wrap_mode = Some(egui::TextWrapMode::Extend);
-            style.spacing.window_margin = Margin::same(0);
-        });
+        let rows = vec![
+            (tr!("document-sidebar-file-path"), self.path.display().to_string()),
+            (
+                tr!("document-sidebar-text-length"),
+                self.loader
+                    .content()
+                    .map_or(tr!("generic-unknown-value"), |content| content.len().to_string()),
+            ),
+        ];
 
-        let default_style = || Style {
-            padding: length(2.),
-            gap: length(2.),
-            ..Default::default()
-        };
-
-        let mut frame = frame::Frame::new();
-        frame.outer_margin = Margin::same(0);
-        frame.inner_margin = Margin::same(0);
-
-        egui::SidePanel::left(ui.id().with("sidebar"))
-            .resizable(true)
-            .frame(frame)
-            .show_inside(ui, |ui| {
-                egui::ScrollArea::both().show(ui, |ui| {
-                    tui(ui, ui.id().with("grid"))
-                        .reserve_available_width()
-                        .style(Style {
-                            align_items: Some(AlignItems::Stretch),
-                            flex_direction: FlexDirection::Column,
-                            size: Size {
-                                width: percent(1.),
-                                height: auto(),
-                            },
-                            padding: length(0.),
-                            gap: length(0.),
-                            ..default_style()
-                        })
-                        .show(|tui| {
-                            tui.style(Style {
-                                flex_grow: 1.0,
-                                display: Display::Grid,
-                                grid_template_columns: vec![fit_content(percent(1.)), fr(1.)],
-                                grid_template_rows: vec![fr(1.), fr(1.)],
-
-                                // ensure items are centered vertically on rows
-                                align_items: Some(AlignItems::Center),
-                                padding: length(0.),
-                                margin: length(0.),
-                                ..default_style()
-                            })
-                            .add(|tui| {
-                                tui.style(Style { ..default_style() })
-                                    .add_with_border(|tui| {
-                                        tui.label(tr!("document-sidebar-file-path"));
-                                    });
-                                tui.style(Style {
-                                    flex_grow: 1.0,
-                                    ..default_style()
-                                })
-                                .add_with_border(|tui| {
-                                    tui.ui_add(egui::Label::new(self.path.display().to_string()))
-                                });
-                                tui.style(Style { ..default_style() })
-                                    .add_with_border(|tui| {
-                                        tui.label(tr!("document-sidebar-text-length"));
-                                    });
-                                tui.style(Style {
-                                    flex_grow: 1.0,
-                                    ..default_style()
-                                })
-                                .add_with_border(|tui| {
-                                    let label_content = self
-                                        .loader
-                                        .content()
-                                        .map_or(tr!("generic-unknown-value"), |content| {
-                                            content.len().to_string()
-                                        });
-                                    tui.label(label_content);
-                                });
-                                // end of grid content
-                            });
-                            // end of container content
-                        });
-                    // end of scroll content
-                });
-                // end of sidebar content
-            });
+        sidebar_ui(ui, rows, |_ui| {});
 
         egui::CentralPanel::default().show_inside(ui, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
@@ -140,7 +84,30 @@ impl TextDocument {
             ui.label(tr!("file-loading-error"));
         } else {
             if let Some(content) = self.loader.content_mut() {
-                ui.add_sized(ui.available_size(), TextEdit::multiline(content));
+                // the same as `add_sized`, but the output is needed to move the cursor.
+                let layout = Layout::centered_and_justified(ui.layout().main_dir());
+                let mut output = ui
+                    .allocate_ui_with_layout(ui.available_size(), layout, |ui| TextEdit::multiline(content).show(ui))
+                    .inner;
+
+                if let Some(line) = self.go_to_line.take() {
+                    let index = content
+                        .split_inclusive('\n')
+                        .take(line.saturating_sub(1))
+                        .map(|line| line.chars().count())
+                        .sum();
+                    let cursor = CCursor::new(index);
+
+                    output.state.cursor.set_char_range(Some(CCursorRange::one(cursor)));
+                    output.state.store(ui.ctx(), output.response.id);
+                    output.response.request_focus();
+
+                    let cursor_rect = output
+                        .galley
+                        .pos_from_cursor(cursor)
+                        .translate(output.galley_pos.to_vec2());
+                    ui.scroll_to_rect(cursor_rect, Some(Align::Center));
+                }
             } else {
                 ui.spinner();
                 ui.label(tr!("file-loading"));
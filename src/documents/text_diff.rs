@@ -0,0 +1,420 @@
+use egui::text::LayoutJob;
+use egui::{vec2, Color32, Key, Label, Modifiers, Rect, RichText, ScrollArea, TextFormat, TextStyle, Ui};
+use egui_i18n::tr;
+use similar::{ChangeTag, DiffOp};
+
+/// the number of unchanged lines shown before and after each change.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DiffLayout {
+    #[default]
+    SideBySide,
+    Unified,
+}
+
+impl DiffLayout {
+    const ALL: [DiffLayout; 2] = [DiffLayout::SideBySide, DiffLayout::Unified];
+
+    fn label(&self) -> String {
+        match self {
+            DiffLayout::SideBySide => tr!("diff-layout-side-by-side"),
+            DiffLayout::Unified => tr!("diff-layout-unified"),
+        }
+    }
+}
+
+struct DiffLine {
+    tag: ChangeTag,
+    /// 1-based, `None` for inserted lines.
+    old_number: Option<usize>,
+    /// 1-based, `None` for deleted lines.
+    new_number: Option<usize>,
+    /// the text of the line, without the line ending, the flag is set for the parts that changed within the line.
+    segments: Vec<(bool, String)>,
+}
+
+impl DiffLine {
+    fn prefix(&self) -> &'static str {
+        match self.tag {
+            ChangeTag::Equal => " ",
+            ChangeTag::Delete => "-",
+            ChangeTag::Insert => "+",
+        }
+    }
+}
+
+struct Hunk {
+    header: String,
+    /// the index of the hunk's header in `DiffContent::unified_rows`
+    unified_row: usize,
+    /// the index of the hunk's header in `DiffContent::side_by_side_rows`
+    side_by_side_row: usize,
+}
+
+/// all rows have the same height, so only the visible rows are shown.
+#[derive(Clone, Copy)]
+enum Row {
+    /// index into `hunks`
+    Header(usize),
+    /// index into `lines`
+    Line(usize),
+    /// indexes into `lines`, deleted lines are on the left, inserted lines on the right.
+    Pair(Option<usize>, Option<usize>),
+}
+
+#[derive(Default)]
+struct DiffContent {
+    lines: Vec<DiffLine>,
+    hunks: Vec<Hunk>,
+    unified_rows: Vec<Row>,
+    side_by_side_rows: Vec<Row>,
+    insertions: usize,
+    deletions: usize,
+    /// the number of digits required for the largest line number.
+    number_width: usize,
+}
+
+impl DiffContent {
+    fn compute(old: &str, new: &str) -> Self {
+        let diff = similar::TextDiff::from_lines(old, new);
+        let mut content = Self {
+            number_width: old.lines().count().max(new.lines().count()).to_string().len(),
+            ..Self::default()
+        };
+
+        for group in diff.grouped_ops(CONTEXT_LINES) {
+            let hunk_index = content.hunks.len();
+            content.hunks.push(Hunk {
+                header: hunk_header(&group),
+                unified_row: content.unified_rows.len(),
+                side_by_side_row: content.side_by_side_rows.len(),
+            });
+            content.unified_rows.push(Row::Header(hunk_index));
+            content.side_by_side_rows.push(Row::Header(hunk_index));
+
+            // changed lines are paired up, so that a modified line is shown next to the line it replaces.
+            let mut deleted = Vec::new();
+            let mut inserted = Vec::new();
+
+            for op in &group {
+                for change in diff.iter_inline_changes(op) {
+                    let index = content.lines.len();
+                    let segments = change
+                        .iter_strings_lossy()
+                        .map(|(emphasized, value)| (emphasized, value.trim_end_matches(['\r', '\n']).to_string()))
+                        .collect();
+
+                    content.lines.push(DiffLine {
+                        tag: change.tag(),
+                        old_number: change.old_index().map(|index| index + 1),
+                        new_number: change.new_index().map(|index| index + 1),
+                        segments,
+                    });
+                    content.unified_rows.push(Row::Line(index));
+
+                    match change.tag() {
+                        ChangeTag::Equal => {
+                            pair_rows(&mut content.side_by_side_rows, &mut deleted, &mut inserted);
+                            content.side_by_side_rows.push(Row::Pair(Some(index), Some(index)));
+                        }
+                        ChangeTag::Delete => {
+                            content.deletions += 1;
+                            deleted.push(index);
+                        }
+                        ChangeTag::Insert => {
+                            content.insertions += 1;
+                            inserted.push(index);
+                        }
+                    }
+                }
+            }
+            pair_rows(&mut content.side_by_side_rows, &mut deleted, &mut inserted);
+        }
+
+        content
+    }
+}
+
+fn pair_rows(rows: &mut Vec<Row>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>) {
+    for index in 0..deleted.len().max(inserted.len()) {
+        rows.push(Row::Pair(deleted.get(index).copied(), inserted.get(index).copied()));
+    }
+    deleted.clear();
+    inserted.clear();
+}
+
+/// a unified diff style header, e.g. `@@ -12,7 +12,8 @@`
+fn hunk_header(group: &[DiffOp]) -> String {
+    let (Some(first), Some(last)) = (group.first(), group.last()) else {
+        return String::new();
+    };
+    let old = first.old_range().start..last.old_range().end;
+    let new = first.new_range().start..last.new_range().end;
+
+    format!("@@ -{},{} +{},{} @@", old.start + 1, old.len(), new.start + 1, new.len())
+}
+
+struct DiffColors {
+    inserted: Color32,
+    deleted: Color32,
+    inserted_emphasis: Color32,
+    deleted_emphasis: Color32,
+}
+
+impl Default for DiffColors {
+    fn default() -> Self {
+        // translucent, so they work with both the light and dark themes.
+        let green = Color32::from_rgb(0x2e, 0xa0, 0x43);
+        let red = Color32::from_rgb(0xda, 0x36, 0x33);
+
+        Self {
+            inserted: green.gamma_multiply(0.2),
+            deleted: red.gamma_multiply(0.2),
+            inserted_emphasis: green.gamma_multiply(0.5),
+            deleted_emphasis: red.gamma_multiply(0.5),
+        }
+    }
+}
+
+impl DiffColors {
+    fn line(&self, tag: ChangeTag) -> Option<Color32> {
+        match tag {
+            ChangeTag::Equal => None,
+            ChangeTag::Delete => Some(self.deleted),
+            ChangeTag::Insert => Some(self.inserted),
+        }
+    }
+
+    fn emphasis(&self, tag: ChangeTag) -> Color32 {
+        match tag {
+            ChangeTag::Equal => Color32::TRANSPARENT,
+            ChangeTag::Delete => self.deleted_emphasis,
+            ChangeTag::Insert => self.inserted_emphasis,
+        }
+    }
+}
+
+/// Shows the differences between two texts, line-by-line, with the changes within lines highlighted.
+///
+/// The diff is only re-computed when either text changes.
+#[derive(Default)]
+pub struct TextDiffView {
+    old: String,
+    new: String,
+    content: DiffContent,
+    colors: DiffColors,
+    layout: DiffLayout,
+    current_hunk: usize,
+    scroll_to_hunk: bool,
+    /// the view was clicked, and nothing else has been clicked since.
+    focused: bool,
+    /// the view was hovered or focused on the last frame, the shortcuts only apply to this view then.
+    active: bool,
+}
+
+impl TextDiffView {
+    pub fn update(&mut self, old: &str, new: &str) {
+        if self.old == old && self.new == new {
+            return;
+        }
+
+        self.old = old.to_string();
+        self.new = new.to_string();
+        self.content = DiffContent::compute(&self.old, &self.new);
+        self.current_hunk = self.current_hunk.min(self.content.hunks.len().saturating_sub(1));
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let response = ui.scope(|ui| self.content_ui(ui)).response;
+
+        // other tabs also have shortcuts, e.g. other diffs, so they're only consumed by the active view.
+        let hovered = response.contains_pointer();
+        if ui.input(|input| input.pointer.any_pressed()) {
+            self.focused = hovered;
+        }
+        self.active = hovered || self.focused;
+    }
+
+    fn content_ui(&mut self, ui: &mut Ui) {
+        let hunk_count = self.content.hunks.len();
+
+        ui.horizontal(|ui| {
+            for layout in DiffLayout::ALL {
+                if ui
+                    .selectable_value(&mut self.layout, layout, layout.label())
+                    .changed()
+                {
+                    // the rows are different for each layout
+                    self.scroll_to_hunk = true;
+                }
+            }
+            ui.separator();
+
+            let previous_button = ui
+                .add_enabled(self.current_hunk > 0, egui::Button::new(tr!("diff-previous-hunk")))
+                .on_hover_text("Alt+↑");
+            let next_button = ui
+                .add_enabled(self.current_hunk + 1 < hunk_count, egui::Button::new(tr!("diff-next-hunk")))
+                .on_hover_text("Alt+↓");
+
+            let (previous_key, next_key) = if self.active {
+                ui.input_mut(|input| {
+                    (
+                        input.consume_key(Modifiers::ALT, Key::ArrowUp),
+                        input.consume_key(Modifiers::ALT, Key::ArrowDown),
+                    )
+                })
+            } else {
+                (false, false)
+            };
+
+            if (previous_button.clicked() || previous_key) && self.current_hunk > 0 {
+                self.current_hunk -= 1;
+                self.scroll_to_hunk = true;
+            }
+            if (next_button.clicked() || next_key) && self.current_hunk + 1 < hunk_count {
+                self.current_hunk += 1;
+                self.scroll_to_hunk = true;
+            }
+
+            if hunk_count > 0 {
+                ui.label(format!("{} {} / {}", tr!("diff-hunk"), self.current_hunk + 1, hunk_count));
+            }
+            ui.separator();
+
+            ui.colored_label(self.colors.inserted_emphasis, format!("+{}", self.content.insertions));
+            ui.colored_label(self.colors.deleted_emphasis, format!("-{}", self.content.deletions));
+        });
+        ui.separator();
+
+        if hunk_count == 0 {
+            ui.label(tr!("diff-identical"));
+            return;
+        }
+
+        let rows = match self.layout {
+            DiffLayout::SideBySide => &self.content.side_by_side_rows,
+            DiffLayout::Unified => &self.content.unified_rows,
+        };
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+
+        // side-by-side lines are truncated to fit the columns, unified lines can be scrolled horizontally.
+        let mut scroll_area = ScrollArea::new([self.layout == DiffLayout::Unified, true])
+            .auto_shrink([false, false]);
+
+        if self.scroll_to_hunk {
+            self.scroll_to_hunk = false;
+
+            let hunk = &self.content.hunks[self.current_hunk];
+            let row = match self.layout {
+                DiffLayout::SideBySide => hunk.side_by_side_row,
+                DiffLayout::Unified => hunk.unified_row,
+            };
+            let offset = row as f32 * (row_height + ui.spacing().item_spacing.y);
+            scroll_area = scroll_area.vertical_scroll_offset(offset);
+        }
+
+        scroll_area.show_rows(ui, row_height, rows.len(), |ui, range| {
+            for row in &rows[range] {
+                match *row {
+                    Row::Header(index) => {
+                        let text = RichText::new(&self.content.hunks[index].header).monospace();
+                        let text = match index == self.current_hunk {
+                            true => text.color(ui.visuals().selection.stroke.color),
+                            false => text.weak(),
+                        };
+                        ui.label(text);
+                    }
+                    Row::Line(index) => {
+                        let line = &self.content.lines[index];
+                        self.line_ui(ui, line, None, row_height);
+                    }
+                    Row::Pair(old, new) => {
+                        ui.columns(2, |columns| {
+                            let old = old.map(|index| &self.content.lines[index]);
+                            let new = new.map(|index| &self.content.lines[index]);
+                            self.side_ui(&mut columns[0], old, |line| line.old_number, row_height);
+                            self.side_ui(&mut columns[1], new, |line| line.new_number, row_height);
+                        });
+                    }
+                }
+            }
+        });
+    }
+
+    /// one side of a side-by-side row, `None` if there is no corresponding line on this side.
+    fn side_ui(&self, ui: &mut Ui, line: Option<&DiffLine>, number: fn(&DiffLine) -> Option<usize>, row_height: f32) {
+        match line {
+            Some(line) => self.line_ui(ui, line, Some(number(line)), row_height),
+            None => {
+                let rect = Rect::from_min_size(ui.cursor().min, vec2(ui.available_width(), row_height));
+                ui.painter().rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+                ui.allocate_rect(rect, egui::Sense::hover());
+            }
+        }
+    }
+
+    /// `side_number` is the line number for one side of a side-by-side row, `None` for unified rows, which show both
+    /// line numbers.
+    fn line_ui(&self, ui: &mut Ui, line: &DiffLine, side_number: Option<Option<usize>>, row_height: f32) {
+        // the background is painted first, so it is behind the text, and extends to the right edge of the view.
+        if let Some(color) = self.colors.line(line.tag) {
+            let min = ui.cursor().min;
+            let right = ui.clip_rect().right().max(min.x);
+            let rect = Rect::from_min_max(min, egui::pos2(right, min.y + row_height));
+            ui.painter().rect_filled(rect, 0.0, color);
+        }
+
+        let width = self.content.number_width;
+        let number_text = |number: Option<usize>| match number {
+            Some(number) => format!("{:>width$}", number, width = width),
+            None => " ".repeat(width),
+        };
+
+        ui.horizontal(|ui| {
+            match side_number {
+                Some(number) => {
+                    ui.label(RichText::new(number_text(number)).monospace().weak());
+                }
+                None => {
+                    ui.label(RichText::new(number_text(line.old_number)).monospace().weak());
+                    ui.label(RichText::new(number_text(line.new_number)).monospace().weak());
+                }
+            }
+            ui.label(RichText::new(line.prefix()).monospace());
+
+            let label = Label::new(self.line_job(ui, line));
+            match side_number {
+                Some(_) => ui.add(label.truncate()),
+                None => ui.add(label.extend()),
+            };
+        });
+    }
+
+    fn line_job(&self, ui: &Ui, line: &DiffLine) -> LayoutJob {
+        let font_id = TextStyle::Monospace.resolve(ui.style());
+        let color = ui.visuals().text_color();
+
+        let mut job = LayoutJob::default();
+        for (emphasized, text) in &line.segments {
+            let background = match emphasized {
+                true => self.colors.emphasis(line.tag),
+                false => Color32::TRANSPARENT,
+            };
+            job.append(
+                text,
+                0.0,
+                TextFormat {
+                    font_id: font_id.clone(),
+                    color,
+                    background,
+                    ..Default::default()
+                },
+            );
+        }
+
+        job
+    }
+}
@@ -3,9 +3,9 @@ use std::sync::{Arc, Mutex};
 
 /// A file picker
 /// * prevents multiple concurrent pick operations
-/// * provides an API convenient for UI usage (see `is_picking` and `picked`)
+/// * provides an API convenient for UI usage (see `is_picking`, `picked` and `picked_files`)
 ///
-/// Currently only picks files, but the API could be expanded.
+/// Picks files, folders and save locations.
 #[derive(Default)]
 pub struct Picker {
     state: PickerState,
@@ -15,8 +15,15 @@ pub struct Picker {
 enum PickerState {
     #[default]
     Pending,
-    // use a boolean to indicate of picking has completed
-    Picking(Arc<Mutex<(bool, Option<PathBuf>)>>),
+    // use a boolean to indicate of picking has completed, no paths are picked when cancelled.
+    Picking(Arc<Mutex<(bool, Vec<PathBuf>)>>),
+}
+
+/// A named group of extensions shown in the dialog, extensions are without the leading `.`, e.g. `png`
+#[derive(Clone, Debug)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -31,11 +38,11 @@ impl Picker {
         matches!(self.state, PickerState::Picking(_))
     }
 
-    fn prepare(&mut self, some_fn: fn() -> Option<PathBuf>) {
+    fn prepare(&mut self, some_fn: impl FnOnce() -> Vec<PathBuf> + Send + 'static) {
         // initialise the boolean flag in the mutex to false, so that when the main thread continues it can see a
         // file has not been picked yet.  note that the mutex may not be locked until the picker thread starts to run
         // and lock it.
-        let picker = Arc::new(Mutex::new((false, None)));
+        let picker = Arc::new(Mutex::new((false, Vec::new())));
         self.state = PickerState::Picking(picker.clone());
         std::thread::Builder::new()
             .name("picker".to_owned())
@@ -50,7 +57,21 @@ impl Picker {
         self.prepare(|| {
             rfd::FileDialog::new()
                 .pick_file()
-                .map(std::path::PathBuf::from)
+                .into_iter()
+                .collect()
+        });
+    }
+
+    /// pick one or more files, use `picked_files` to get all of them.
+    pub fn pick_files(&mut self, filters: Vec<FileFilter>) {
+        self.prepare(move || {
+            let mut dialog = rfd::FileDialog::new();
+            for filter in filters {
+                dialog = dialog.add_filter(filter.name, &filter.extensions);
+            }
+            dialog
+                .pick_files()
+                .unwrap_or_default()
         });
     }
 
@@ -58,14 +79,40 @@ impl Picker {
         self.prepare(|| {
             rfd::FileDialog::new()
                 .pick_folder()
-                .map(std::path::PathBuf::from)
+                .into_iter()
+                .collect()
+        });
+    }
+
+    /// pick a location to save a file, `extensions` are without the leading `.`, e.g. `png`
+    pub fn save_file(&mut self, directory: Option<PathBuf>, file_name: String, filter_name: String, extensions: Vec<String>) {
+        self.prepare(move || {
+            let mut dialog = rfd::FileDialog::new()
+                .set_file_name(file_name)
+                .add_filter(filter_name, &extensions);
+            if let Some(directory) = directory {
+                dialog = dialog.set_directory(directory);
+            }
+            dialog
+                .save_file()
+                .into_iter()
+                .collect()
         });
     }
 
     /// when picked, returns the picked path, or an error indicating the reason
     ///
-    /// this method is designed to be very fast while the picker is not picking (pending)
+    /// if multiple files were picked, only the first is returned, see `picked_files`.
     pub fn picked(&mut self) -> Result<PathBuf, PickError> {
+        self.picked_files()
+            // never empty, cancelling is an error
+            .map(|paths| paths.into_iter().next().unwrap())
+    }
+
+    /// when picked, returns the picked paths, or an error indicating the reason
+    ///
+    /// this method is designed to be very fast while the picker is not picking (pending)
+    pub fn picked_files(&mut self) -> Result<Vec<PathBuf>, PickError> {
         let mut was_picked = false;
 
         let result = match &mut self.state {
@@ -74,9 +121,9 @@ impl Picker {
                     match &mut *guard {
                         (true, picked) => {
                             was_picked = true;
-                            match picked.take() {
-                                Some(picked_path) => Ok(picked_path),
-                                None => Err(PickError::Cancelled),
+                            match std::mem::take(picked) {
+                                picked_paths if picked_paths.is_empty() => Err(PickError::Cancelled),
+                                picked_paths => Ok(picked_paths),
                             }
                         }
                         // arc not locked, but not picked yet either
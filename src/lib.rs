@@ -1,9 +1,11 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub mod args;
 pub mod context;
 pub mod documents;
 pub mod file_picker;
 pub mod fonts;
 pub mod i18n;
+pub mod single_instance;
 pub use app::TemplateApp;
@@ -6,8 +6,27 @@ use tabbed_document_ui_example::i18n;
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
+    use tabbed_document_ui_example::args::{Args, USAGE};
+    use tabbed_document_ui_example::single_instance;
+
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    let args = match Args::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(cause) => {
+            eprintln!("{}\n\n{}", cause, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    if args.single_instance && !args.new_window && single_instance::forward(&args) {
+        return Ok(());
+    }
+
     i18n::init();
 
     let native_options = eframe::NativeOptions {
@@ -24,7 +43,7 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "Tabbed document UI",
         native_options,
-        Box::new(|cc| Ok(Box::new(tabbed_document_ui_example::TemplateApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(tabbed_document_ui_example::TemplateApp::new(cc, args)))),
     )
 }
 
@@ -32,6 +51,7 @@ fn main() -> eframe::Result {
 #[cfg(target_arch = "wasm32")]
 fn main() {
     use eframe::wasm_bindgen::JsCast as _;
+    use tabbed_document_ui_example::args::Args;
 
     // Redirect `log` message to `console.log` and friends:
     eframe::WebLogger::init(log::LevelFilter::Debug).ok();
@@ -54,7 +74,8 @@ fn main() {
             .start(
                 canvas,
                 web_options,
-                Box::new(|cc| Ok(Box::new(tabbed_document_ui_example::TemplateApp::new(cc)))),
+                // there is no command line on the web.
+                Box::new(|cc| Ok(Box::new(tabbed_document_ui_example::TemplateApp::new(cc, Args::default())))),
             )
             .await;
 
@@ -0,0 +1,328 @@
+//! Single-instance mode, see the `--single-instance` option.
+//!
+//! The first instance listens on a per-user local socket, later instances forward their files to it and exit.
+//! On Unix this is a Unix domain socket in the user's runtime directory, on Windows it's a named pipe.
+//!
+//! The request is the handshake, the length of the body, then the body, which is a list of records.  Each record
+//! is a tag, the length of the value, then the value; paths are sent as their platform bytes, not as text, so any
+//! path can be sent.  The reply is `ok`.
+
+use crate::app::{AppMessage, AppMessageSender, MessageSource};
+use crate::args::Args;
+use log::{error, info, warn};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+const HANDSHAKE: &[u8] = b"tabbed_document_ui_example/1";
+const REPLY: &[u8] = b"ok";
+/// requests are small, a larger request is rejected instead of being read into memory.
+const MAX_REQUEST_LENGTH: u32 = 1024 * 1024;
+
+const TAG_FILE: u8 = b'F';
+const TAG_LINE: u8 = b'L';
+
+/// returns `true` if the files were forwarded to a running instance, in which case this instance should exit.
+pub fn forward(args: &Args) -> bool {
+    let stream = match platform::connect() {
+        Ok(stream) => stream,
+        Err(cause) => {
+            info!("No running instance found. cause: {}", cause);
+            return false;
+        }
+    };
+
+    match send_request(stream, args) {
+        Ok(()) => {
+            info!("Files forwarded to the running instance. files: {:?}", args.files);
+            true
+        }
+        Err(cause) => {
+            error!("Failed to forward files to the running instance. cause: {}", cause);
+            false
+        }
+    }
+}
+
+fn send_request(mut stream: impl Read + Write, args: &Args) -> std::io::Result<()> {
+    let mut body = vec![];
+    let mut add_record = |tag: u8, value: &[u8]| {
+        body.push(tag);
+        body.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        body.extend_from_slice(value);
+    };
+
+    if let Some(line) = args.line {
+        add_record(TAG_LINE, line.to_string().as_bytes());
+    }
+    for path in &args.files {
+        // the running instance has a different current directory.
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+        add_record(TAG_FILE, &platform::path_to_bytes(&path));
+    }
+
+    if body.len() > MAX_REQUEST_LENGTH as usize {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "too many files"));
+    }
+
+    stream.write_all(HANDSHAKE)?;
+    stream.write_all(&(body.len() as u32).to_le_bytes())?;
+    stream.write_all(&body)?;
+    stream.flush()?;
+
+    let mut reply = [0; REPLY.len()];
+    stream.read_exact(&mut reply)?;
+    if reply != REPLY {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "unexpected reply"));
+    }
+
+    Ok(())
+}
+
+/// listens for other instances in a background thread, their files are sent to the app as `AppMessage::OpenFiles`.
+pub(crate) fn listen(sender: AppMessageSender) {
+    let result = platform::listen(move |stream| match receive_request(stream) {
+        Ok(Some((files, line))) => {
+            info!("Files received from another instance. files: {:?}", files);
+            sender
+                .send((MessageSource::OtherInstance, AppMessage::OpenFiles(files, line)))
+                .expect("sent");
+        }
+        Ok(None) => warn!("Ignoring a connection that is not from another instance."),
+        Err(cause) => error!("Failed to receive files from another instance. cause: {}", cause),
+    });
+
+    if let Err(cause) = result {
+        error!("Failed to listen for other instances. cause: {}", cause);
+    }
+}
+
+/// `None` if the request did not start with the handshake.
+fn receive_request(mut stream: impl Read + Write) -> std::io::Result<Option<(Vec<PathBuf>, Option<usize>)>> {
+    let mut handshake = [0; HANDSHAKE.len()];
+    stream.read_exact(&mut handshake)?;
+    if handshake != HANDSHAKE {
+        return Ok(None);
+    }
+
+    let mut length = [0; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length);
+    if length > MAX_REQUEST_LENGTH {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "request too large"));
+    }
+
+    let mut body = vec![0; length as usize];
+    stream.read_exact(&mut body)?;
+
+    let mut files = vec![];
+    let mut line = None;
+    let mut remaining = body.as_slice();
+    while let Some((&tag, rest)) = remaining.split_first() {
+        let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid record");
+
+        let (value_length, rest) = rest.split_first_chunk::<4>().ok_or_else(invalid)?;
+        let value_length = u32::from_le_bytes(*value_length) as usize;
+        let value = rest.get(..value_length).ok_or_else(invalid)?;
+        remaining = &rest[value_length..];
+
+        match tag {
+            TAG_FILE => match platform::path_from_bytes(value) {
+                Some(path) => files.push(path),
+                None => warn!("Ignoring an invalid path."),
+            },
+            TAG_LINE => {
+                line = std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+            }
+            _ => warn!("Ignoring an unknown record. tag: {}", tag),
+        }
+    }
+
+    stream.write_all(REPLY)?;
+    stream.flush()?;
+
+    Ok(Some((files, line)))
+}
+
+#[cfg(unix)]
+mod platform {
+    use log::{info, warn};
+    use std::ffi::OsStr;
+    use std::fs::{DirBuilder, Permissions};
+    use std::io::ErrorKind;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+    use std::time::Duration;
+
+    const SOCKET_NAME: &str = "tabbed_document_ui_example.sock";
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// `$XDG_RUNTIME_DIR` is only accessible to the user, otherwise a directory that only the user can access is
+    /// created in the temporary directory.
+    fn socket_path() -> std::io::Result<PathBuf> {
+        if let Some(runtime_directory) = std::env::var_os("XDG_RUNTIME_DIR") {
+            return Ok(PathBuf::from(runtime_directory).join(SOCKET_NAME));
+        }
+
+        let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+        let directory = std::env::temp_dir().join(format!("tabbed_document_ui_example-{}", user));
+        match DirBuilder::new().mode(0o700).create(&directory) {
+            Ok(()) => {}
+            Err(cause) if cause.kind() == ErrorKind::AlreadyExists => {}
+            Err(cause) => return Err(cause),
+        }
+
+        // the directory may have been created by someone else.
+        let metadata = std::fs::symlink_metadata(&directory)?;
+        if !metadata.is_dir() || metadata.permissions().mode() & 0o077 != 0 {
+            return Err(std::io::Error::new(
+                ErrorKind::PermissionDenied,
+                format!("directory is accessible to other users: {:?}", directory),
+            ));
+        }
+
+        Ok(directory.join(SOCKET_NAME))
+    }
+
+    pub fn connect() -> std::io::Result<UnixStream> {
+        let stream = UnixStream::connect(socket_path()?)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        Ok(stream)
+    }
+
+    pub fn listen(handle: impl Fn(UnixStream) + Send + 'static) -> std::io::Result<()> {
+        let path = socket_path()?;
+
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(std::io::Error::new(ErrorKind::AddrInUse, "another instance is listening"));
+            }
+            // left behind by an instance that did not exit cleanly.
+            warn!("Removing stale socket. path: {:?}", path);
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        std::fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+        info!("Listening for other instances. path: {:?}", path);
+
+        thread::Builder::new()
+            .name("single instance".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = stream.and_then(|stream| {
+                        stream.set_read_timeout(Some(TIMEOUT))?;
+                        stream.set_write_timeout(Some(TIMEOUT))?;
+                        Ok(stream)
+                    });
+                    match stream {
+                        Ok(stream) => handle(stream),
+                        Err(cause) => warn!("Failed to accept connection. cause: {}", cause),
+                    }
+                }
+            })?;
+
+        Ok(())
+    }
+
+    pub fn path_to_bytes(path: &Path) -> Vec<u8> {
+        path.as_os_str().as_bytes().to_vec()
+    }
+
+    pub fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+        Some(PathBuf::from(OsStr::from_bytes(bytes)))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use interprocess::local_socket::prelude::*;
+    use interprocess::local_socket::{GenericNamespaced, ListenerOptions, Name, Stream};
+    use log::{info, warn};
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+
+    /// named pipes are per-session, the user name is added so each user has their own pipe.
+    fn pipe_name() -> std::io::Result<Name<'static>> {
+        let user = std::env::var("USERNAME").unwrap_or_else(|_| "user".to_string());
+        format!("tabbed_document_ui_example-{}", user).to_ns_name::<GenericNamespaced>()
+    }
+
+    pub fn connect() -> std::io::Result<Stream> {
+        Stream::connect(pipe_name()?)
+    }
+
+    pub fn listen(handle: impl Fn(Stream) + Send + 'static) -> std::io::Result<()> {
+        let listener = ListenerOptions::new()
+            .name(pipe_name()?)
+            .create_sync()?;
+        info!("Listening for other instances.");
+
+        thread::Builder::new()
+            .name("single instance".to_owned())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => handle(stream),
+                        Err(cause) => warn!("Failed to accept connection. cause: {}", cause),
+                    }
+                }
+            })?;
+
+        Ok(())
+    }
+
+    /// UTF-16, little-endian
+    pub fn path_to_bytes(path: &Path) -> Vec<u8> {
+        path.as_os_str()
+            .encode_wide()
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    pub fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+        if bytes.len() % 2 != 0 {
+            return None;
+        }
+        let wide = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+
+        Some(PathBuf::from(OsString::from_wide(&wide)))
+    }
+}
+
+/// e.g. the web build, there are no other instances.
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::path::{Path, PathBuf};
+
+    fn unsupported() -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::Unsupported, "single instance mode is not supported")
+    }
+
+    pub fn connect() -> std::io::Result<std::io::Empty> {
+        Err(unsupported())
+    }
+
+    pub fn listen(_handle: impl Fn(std::io::Empty) + Send + 'static) -> std::io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn path_to_bytes(path: &Path) -> Vec<u8> {
+        path.to_string_lossy().as_bytes().to_vec()
+    }
+
+    pub fn path_from_bytes(bytes: &[u8]) -> Option<PathBuf> {
+        std::str::from_utf8(bytes).ok().map(PathBuf::from)
+    }
+}